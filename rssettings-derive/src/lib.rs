@@ -0,0 +1,223 @@
+//! Derive macro of the rssettings::SettingsSection trait, binding a section
+//! of a settings file to a struct with named fields.
+//!
+//! Attributes:
+//! * `#[settings(section = "NAME")]` on the struct: the name of the section,
+//!   the struct name if missing
+//! * `#[settings(rename = "key")]` on a field: the key of the field,
+//!   the field name if missing
+//! * `#[settings(default)]` or `#[settings(default = "expression")]` on a field:
+//!   the value used when the key (or the section) is missing,
+//!   Default::default() or the expression
+//!
+//! Fields are read with Settings::get_value and written with Settings::set_or_insert,
+//! so their types have to implement FromStr and Display. Option fields are None
+//! when the key is missing and are not written when None.
+//! from_settings reports all the missing and invalid keys at once with
+//! SettingsError::InvalidSection.
+//!
+//! # Examples
+//! ```
+//! use rssettings::{Settings, SettingsSection};
+//!
+//! #[derive(rssettings_derive::SettingsSection)]
+//! #[settings(section = "LOG")]
+//! struct Log {
+//!     enabled: bool,
+//!     #[settings(rename = "log_level", default = "3")]
+//!     level: u8,
+//!     file: Option<String>,
+//! }
+//!
+//! fn main() {
+//!     let mut settings: Settings = "[LOG]\nenabled = true\n".parse().unwrap();
+//!     let mut log = Log::from_settings(&settings).unwrap();
+//!     assert_eq!(3, log.level);
+//!     assert_eq!(None, log.file);
+//!     log.level = 5;
+//!     log.write_to_settings(&mut settings).unwrap();
+//!     assert_eq!(5, settings.get("LOG", "log_level", 0).value);
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, GenericArgument, LitStr, PathArguments, Type};
+
+const ATTRIBUTE_NAME: &str = "settings";
+
+/// Derives rssettings::SettingsSection, see the crate documentation
+#[proc_macro_derive(SettingsSection, attributes(settings))]
+pub fn derive_settings_section(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into()
+    }
+}
+
+// The binding of a struct field to a key
+struct FieldBinding {
+    ident: syn::Ident,
+    key: String,
+    // the type parsed by Settings::get_value, the inner type of Option fields
+    value_type: Type,
+    optional: bool,
+    default: Option<Expr>,
+}
+
+// Returns the implementation of SettingsSection for the struct
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "SettingsSection can only be derived for structs with named fields"))
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "SettingsSection can only be derived for structs"))
+    };
+    let section = section_name(input)?;
+    let bindings = fields.iter().map(|field| {
+        let ident = field.ident.clone().expect("named field");
+        let mut key = ident.unraw().to_string();
+        let mut default = None;
+        for attribute in field.attrs.iter().filter(|attribute| attribute.path().is_ident(ATTRIBUTE_NAME)) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    key = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    default = Some(match meta.value() {
+                        Ok(value) => value.parse::<LitStr>()?.parse::<Expr>()?,
+                        Err(_) => syn::parse_quote!(::std::default::Default::default())
+                    });
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported settings field attribute, expected `rename` or `default`"))
+                }
+            })?;
+        }
+        let (value_type, optional) = match option_inner_type(&field.ty) {
+            Some(inner_type) => (inner_type.clone(), true),
+            None => (field.ty.clone(), false)
+        };
+        Ok(FieldBinding { ident, key, value_type, optional, default })
+    }).collect::<syn::Result<Vec<FieldBinding>>>()?;
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let reads = bindings.iter().map(|binding| read_field(binding, &section));
+    let writes = bindings.iter().map(|binding| write_field(binding, &section));
+    let idents: Vec<&syn::Ident> = bindings.iter().map(|binding| &binding.ident).collect();
+    let checked: Vec<syn::Ident> = idents.iter().map(|ident| format_ident!("__{}", ident)).collect();
+    let check = if bindings.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            let mut errors: ::std::vec::Vec<::rssettings::SettingsError> = ::std::vec::Vec::new();
+            #(let #checked = #reads;)*
+            let (#(::std::option::Option::Some(#idents),)*) = (#(#checked,)*) else {
+                return ::std::result::Result::Err(settings.invalid_section_error(#section, errors));
+            };
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::rssettings::SettingsSection for #name #type_generics #where_clause {
+            fn from_settings(settings: &::rssettings::Settings) -> ::std::result::Result<Self, ::rssettings::SettingsError> {
+                #check
+                ::std::result::Result::Ok(Self { #(#idents),* })
+            }
+
+            fn write_to_settings(&self, settings: &mut ::rssettings::Settings) -> ::std::result::Result<(), ::rssettings::SettingsError> {
+                settings.add_section(#section)?;
+                #(#writes)*
+                ::std::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+// Returns the section name given by #[settings(section = "NAME")] or the struct name
+fn section_name(input: &DeriveInput) -> syn::Result<String> {
+    let mut section = input.ident.to_string();
+    for attribute in input.attrs.iter().filter(|attribute: &&Attribute| attribute.path().is_ident(ATTRIBUTE_NAME)) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("section") {
+                section = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported settings struct attribute, expected `section`"))
+            }
+        })?;
+    }
+    Ok(section)
+}
+
+// Returns T if the type is Option<T>
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first() {
+        Some(GenericArgument::Type(inner_type)) if arguments.args.len() == 1 => Some(inner_type),
+        _ => None
+    }
+}
+
+// Returns the expression reading a field: Some(value) or None
+// after pushing the error of the missing or invalid key in 'errors'
+fn read_field(binding: &FieldBinding, section: &str) -> TokenStream2 {
+    let key = &binding.key;
+    let value_type = &binding.value_type;
+    let value = if binding.optional {
+        quote!(::std::option::Option::Some(value))
+    } else {
+        quote!(value)
+    };
+    let missing = match (&binding.default, binding.optional) {
+        (Some(default), _) => Some(quote!(::std::option::Option::Some(#default))),
+        (None, true) => Some(quote!(::std::option::Option::Some(::std::option::Option::None))),
+        (None, false) => None
+    };
+    // without default value a missing key is an error as an invalid one
+    let missing_arm = missing.map(|missing| quote! {
+        ::std::result::Result::Err(::rssettings::SettingsError::SectionNotFound { .. }) |
+        ::std::result::Result::Err(::rssettings::SettingsError::KeyNotFound { .. }) => #missing,
+    });
+    quote! {
+        match settings.get_value::<#value_type>(#section, #key) {
+            ::std::result::Result::Ok(value) => ::std::option::Option::Some(#value),
+            #missing_arm
+            ::std::result::Result::Err(error) => {
+                errors.push(error);
+                ::std::option::Option::None
+            }
+        }
+    }
+}
+
+// Returns the statement writing a field, None values are not written
+fn write_field(binding: &FieldBinding, section: &str) -> TokenStream2 {
+    let ident = &binding.ident;
+    let key = &binding.key;
+    if binding.optional {
+        quote! {
+            if let ::std::option::Option::Some(value) = &self.#ident {
+                settings.set_or_insert(#section, #key, value)?;
+            }
+        }
+    } else {
+        quote! {
+            settings.set_or_insert(#section, #key, &self.#ident)?;
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use rssettings::{Settings, SettingsError, SettingsSection};
+
+#[derive(Debug, PartialEq, rssettings_derive::SettingsSection)]
+#[settings(section = "LOG")]
+struct Log {
+    enabled: bool,
+    #[settings(rename = "log_level", default = "3")]
+    level: u8,
+    #[settings(default)]
+    max_size: u32,
+    file: Option<String>,
+    r#type: String,
+}
+
+#[derive(Debug, PartialEq, rssettings_derive::SettingsSection)]
+struct Empty {
+}
+
+#[test]
+fn from_settings_and_write_to_settings() {
+    let mut settings: Settings = "[LOG]\nenabled = true\ntype = text\n".parse().unwrap();
+    let mut log = Log::from_settings(&settings).unwrap();
+    assert_eq!(Log { enabled: true, level: 3, max_size: 0, file: None, r#type: "text".to_string() }, log);
+
+    log.level = 5;
+    log.file = Some("app.log".to_string());
+    assert!(log.write_to_settings(&mut settings).is_ok());
+    let mut buffer: Vec<u8> = vec![];
+    assert!(settings.write_to(&mut buffer).is_ok());
+    assert_eq!("[LOG]\nenabled = true\ntype = text\nlog_level = 5\nmax_size = 0\nfile = app.log\n", String::from_utf8(buffer).unwrap());
+    assert_eq!(log, Log::from_settings(&settings).unwrap());
+
+    assert!(Empty::from_settings(&settings).is_ok());
+    assert!(Empty {}.write_to_settings(&mut settings).is_ok());
+    assert!(settings.section_exists("Empty"));
+}
+
+#[test]
+fn all_errors_at_once() {
+    let settings: Settings = "[LOG]\nenabled = maybe\nlog_level = 300\n".parse().unwrap();
+    match Log::from_settings(&settings) {
+        Err(SettingsError::InvalidSection { section, errors, message }) => {
+            assert_eq!("LOG", section);
+            assert_eq!(3, errors.len());
+            assert!(matches!(errors[0], SettingsError::Parsing { line: 2, .. }));
+            assert!(matches!(errors[1], SettingsError::Parsing { line: 3, .. }));
+            assert!(matches!(errors[2], SettingsError::KeyNotFound { .. }));
+            assert!(message.starts_with("Section 'LOG' has missing or invalid keys: "));
+            assert!(message.contains("at line '2'; "));
+            assert!(message.ends_with("Section 'LOG' key 'type' not found"));
+        },
+        result => panic!("unexpected result {:?}", result.map(|_| ()))
+    }
+
+    let settings = Settings::new();
+    assert!(matches!(Log::from_settings(&settings), Err(SettingsError::InvalidSection { .. })));
+}
@@ -0,0 +1,144 @@
+use crate::{Settings, SettingsError, ASSIGN_TAG, BAD_OVERRIDE_MESSAGE_IDX};
+
+// Module applying to Settings the overrides given on the command line as
+// SECTION.key=value arguments (i.g. --set LOG.level=5): they are kept apart
+// from the sections as the environment overrides, win over them and are never saved.
+
+// Separator of section and key in an override argument
+const SECTION_KEY_SEPARATOR: char = '.';
+// Id and long name of the clap argument giving the overrides (clap feature)
+#[cfg(feature = "clap")]
+const OVERRIDE_ARG_ID: &str = "set";
+
+impl Settings {
+    /// Applies overrides given as "SECTION.key=value" strings (i.g. the values of the
+    /// --set flags of a command line), the key ends at the first '=' and the value is
+    /// taken as it is; the section ends at the first '.' unless "SECTION.key" is the
+    /// path of an existing key in a nested section (see Settings::resolve_path)
+    /// Overriding values are returned by Settings::get, Settings::get_value,
+    /// Settings::get_list and Settings::deserialize in place of the
+    /// file values and of the environment overrides (see Settings::with_env_overrides),
+    /// they are never written by Settings::save and the last override of a key wins
+    /// Overrides are applied all together only if they are all valid
+    /// Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError::BadOverride)
+    /// if a string is not an override, std::result::Result::Err(SettingsError::SectionNotFound) or
+    /// std::result::Result::Err(SettingsError::KeyNotFound) if the key does not exist and
+    /// 'allow_insert' is false
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    ///
+    /// fn main() {
+    ///     let mut settings: Settings = "[LOG]\nlevel = 1\n".parse().unwrap();
+    ///     settings.apply_overrides(["LOG.level=5"], false).unwrap();
+    ///     assert_eq!(5, settings.get("LOG", "level", 0).value);
+    ///     assert!(settings.apply_overrides(["LOG.file=app.log"], false).is_err());
+    ///     assert!(settings.apply_overrides(["LOG.file=app.log"], true).is_ok());
+    /// }
+    /// ```
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `overrides` -  The "SECTION.key=value" strings
+    ///  * `allow_insert` -  If true keys and sections missing in Settings can be overridden
+    ///
+    pub fn apply_overrides<I, S>(&mut self, overrides: I, allow_insert: bool) -> Result<(), SettingsError> where I: IntoIterator<Item = S>, S: AsRef<str> {
+        let mut parsed = vec![];
+        for argument in overrides {
+            let argument = argument.as_ref();
+            let (section_name, key, value) = match Self::parse_override(argument) {
+                Some(arg_override) => arg_override,
+                None => return Err(self.bad_override_error(argument))
+            };
+            let path = format!("{}{}{}", section_name, SECTION_KEY_SEPARATOR, key);
+            let (section_name, key) = self.resolve_path(&path).unwrap_or((section_name, key));
+            if !allow_insert {
+                if !self.section_exists(section_name) {
+                    return Err(self.section_not_found_error(section_name));
+                }
+                if !self.key_exists(section_name, key) {
+                    return Err(self.key_not_found_error(section_name, key));
+                }
+            }
+            parsed.push((section_name.to_string(), key.to_string(), value.to_string()));
+        }
+        self.arg_overrides.extend(parsed);
+        Ok(())
+    }
+
+    /// Removes the overrides applied by Settings::apply_overrides,
+    /// the environment overrides are kept
+    ///  * `self` -  A mutable reference to Setting struct
+    ///
+    pub fn clear_overrides(&mut self) {
+        self.arg_overrides.clear();
+    }
+
+    /// Returns the clap argument giving the overrides of Settings::apply_override_matches,
+    /// a --set flag that can be repeated (available with the clap feature)
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    ///
+    /// fn main() {
+    ///     let command = clap::Command::new("app").arg(Settings::override_arg());
+    ///     let matches = command.get_matches_from(["app", "--set", "LOG.level=5"]);
+    ///     let mut settings: Settings = "[LOG]\nlevel = 1\n".parse().unwrap();
+    ///     settings.apply_override_matches(&matches, false).unwrap();
+    ///     assert_eq!(5, settings.get("LOG", "level", 0).value);
+    /// }
+    /// ```
+    ///
+    #[cfg(feature = "clap")]
+    pub fn override_arg() -> clap::Arg {
+        clap::Arg::new(OVERRIDE_ARG_ID)
+            .long(OVERRIDE_ARG_ID)
+            .value_name("SECTION.key=value")
+            .action(clap::ArgAction::Append)
+            .help("Overrides the value of a settings key")
+    }
+
+    /// Applies the overrides given by the argument of Settings::override_arg,
+    /// see Settings::apply_overrides (available with the clap feature)
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `matches` -  The matches of a command having the argument of Settings::override_arg
+    ///  * `allow_insert` -  If true keys and sections missing in Settings can be overridden
+    ///
+    #[cfg(feature = "clap")]
+    pub fn apply_override_matches(&mut self, matches: &clap::ArgMatches, allow_insert: bool) -> Result<(), SettingsError> {
+        let overrides = matches.get_many::<String>(OVERRIDE_ARG_ID).into_iter().flatten();
+        self.apply_overrides(overrides, allow_insert)
+    }
+
+    // Returns the value overriding a key: the last command line override,
+    // the environment override otherwise (see Settings::with_env_overrides)
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section
+    pub(crate) fn value_override(&self, section_name: &str, key: &str) -> Option<&str> {
+        self.arg_overrides.iter().rev()
+            .find(|(override_section, override_key, _)| {
+                self.name_matching.matches(override_section, section_name) && self.name_matching.matches(override_key, key)
+            })
+            .map(|(_, _, value)| value.as_str())
+            .or_else(|| self.env_override(section_name, key))
+    }
+
+    // Associated function that splits an override in section, key and value
+    // Returns None if the section or the key is empty or the assignment is missing
+    //  * `argument` -  A string slice that holds the "SECTION.key=value" override
+    fn parse_override(argument: &str) -> Option<(&str, &str, &str)> {
+        let (name, value) = argument.split_once(ASSIGN_TAG)?;
+        let (section_name, key) = name.split_once(SECTION_KEY_SEPARATOR)?;
+        let (section_name, key) = (section_name.trim(), key.trim());
+        if section_name.is_empty() || key.is_empty() {
+            return None;
+        }
+        Some((section_name, key, value))
+    }
+
+    // Returns the error of a string that is not an override
+    fn bad_override_error(&self, argument: &str) -> SettingsError {
+        let argument = argument.to_string();
+        let message = self.format_message(BAD_OVERRIDE_MESSAGE_IDX, vec![&argument]);
+        SettingsError::BadOverride { argument, message }
+    }
+}
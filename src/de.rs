@@ -0,0 +1,368 @@
+use std::fmt::{Debug, Display};
+use std::slice::Iter;
+use std::str::FromStr;
+use std::vec::IntoIter;
+
+use serde::de::value::StrDeserializer;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::{KeyValuePair, Section, Settings, SettingsError, ARRAY_TAG, SERDE_MESSAGE_IDX, SETTINGS_MESSAGES};
+
+// Module implementing serde::Deserializer for Settings (serde feature):
+// sections are deserialized as structs (or maps) and their keys as fields,
+// values are interpolated and parsed with their FromStr implementation as Settings::get does,
+// the elements of an array (see Settings::with_array_keys) are a sequence.
+
+impl Settings {
+    /// Deserializes Settings in a struct whose fields are the sections,
+    /// every section is a struct (or a map) whose fields are the keys,
+    /// values are parsed with the FromStr trait as Settings::get does,
+    /// missing sections and keys can be handled with Option fields or #[serde(default)]
+    /// The elements of an array (repeated "key[]" keys, see Settings::with_array_keys)
+    /// are a single field named without [] deserialized as a sequence (i.g. a Vec)
+    /// (available with the serde feature)
+    /// Returns std::result::Result::Ok(T) or std::result::Result::Err(SettingsError::Parsing)
+    /// if a value can not be parsed, std::result::Result::Err(SettingsError::Serde)
+    /// for the other errors (i.g. a missing section or key)
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Log {
+    ///     enabled: bool,
+    ///     level: u8,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// #[allow(non_snake_case)]
+    /// struct AppConfig {
+    ///     LOG: Log,
+    /// }
+    ///
+    /// fn main() {
+    ///     let settings: Settings = "[LOG]\nenabled = true\nlevel = 3\n".parse().unwrap();
+    ///     match settings.deserialize::<AppConfig>() {
+    ///         Result::Ok(config) => {
+    ///             assert!(config.LOG.enabled);
+    ///             assert_eq!(3, config.LOG.level);
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `T` -  The deserialized type
+    ///
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, SettingsError> {
+        T::deserialize(self).map_err(|error| match error {
+            // errors created by serde have english messages
+            SettingsError::Serde { source, .. } => self.serde_error(source),
+            error => error
+        })
+    }
+}
+
+// serde::de::Error trait implementation for SettingsError enumeration,
+// the message uses the default english messages table
+impl de::Error for SettingsError {
+    fn custom<T: Display>(msg: T) -> Self {
+        let source = msg.to_string();
+        let message = SETTINGS_MESSAGES[SERDE_MESSAGE_IDX].replacen("{}", &source, 1);
+        SettingsError::Serde { source, message }
+    }
+}
+
+// Deserializer of the whole Settings, a map from section names to sections
+impl<'de> de::Deserializer<'de> for &Settings {
+    type Error = SettingsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_map(SectionsAccess { settings: self, sections: self.sections.iter(), section: None })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+// Access to the sections of Settings as map entries
+struct SectionsAccess<'a> {
+    settings: &'a Settings,
+    sections: Iter<'a, Section>,
+    section: Option<&'a Section>,
+}
+
+impl<'de, 'a> MapAccess<'de> for SectionsAccess<'a> {
+    type Error = SettingsError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SettingsError> {
+        match self.sections.next() {
+            Some(section) => {
+                self.section = Some(section);
+                let name: StrDeserializer<SettingsError> = section.name.as_str().into_deserializer();
+                seed.deserialize(name).map(Some)
+            },
+            None => Ok(None)
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SettingsError> {
+        match self.section.take() {
+            Some(section) => seed.deserialize(SectionDeserializer { settings: self.settings, section }),
+            None => Err(de::Error::custom("section value requested before its name"))
+        }
+    }
+}
+
+// Deserializer of a section, a map from keys to values
+struct SectionDeserializer<'a> {
+    settings: &'a Settings,
+    section: &'a Section,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for SectionDeserializer<'a> {
+    type Error = SettingsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_map(KeysAccess { settings: self.settings, section: self.section, values: self.section.values.iter(), value: None, arrays: vec![] })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+// Access to the key/value pairs of a section as map entries,
+// the elements of an array are a single entry at the place of the first one
+struct KeysAccess<'a> {
+    settings: &'a Settings,
+    section: &'a Section,
+    values: Iter<'a, KeyValuePair>,
+    value: Option<&'a KeyValuePair>,
+    arrays: Vec<&'a str>,
+}
+
+impl<'a> KeysAccess<'a> {
+    // Returns true if a key is an array element
+    fn is_array(&self, key_value: &KeyValuePair) -> bool {
+        self.settings.array_keys && key_value.key.ends_with(ARRAY_TAG)
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for KeysAccess<'a> {
+    type Error = SettingsError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SettingsError> {
+        while let Some(key_value) = self.values.next() {
+            let mut key = key_value.key.as_str();
+            if self.is_array(key_value) {
+                // the following elements of an array already deserialized are skipped
+                if self.arrays.iter().any(|array| self.settings.name_matching.matches(array, key)) {
+                    continue;
+                }
+                self.arrays.push(key);
+                key = key.strip_suffix(ARRAY_TAG).unwrap_or(key);
+            }
+            self.value = Some(key_value);
+            let key: StrDeserializer<SettingsError> = key.into_deserializer();
+            return seed.deserialize(key).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SettingsError> {
+        match self.value.take() {
+            Some(key_value) if self.is_array(key_value) => {
+                let key = key_value.key.strip_suffix(ARRAY_TAG).unwrap_or(&key_value.key);
+                // an overriding value is a single element
+                let elements = match self.settings.value_override(&self.section.name, key) {
+                    Some(value) => vec![(self.settings.interpolate(&self.section.name, key, 0, value)?.into_owned(), 0)],
+                    None => self.settings.list_elements(&self.section.name, key, "")?
+                };
+                seed.deserialize(ArrayDeserializer {
+                    settings: self.settings,
+                    section: &self.section.name,
+                    key,
+                    elements
+                })
+            },
+            Some(key_value) => {
+                // overrides win over the file values (see Settings::apply_overrides)
+                let (raw_value, line) = match self.settings.value_override(&self.section.name, &key_value.key) {
+                    Some(value) => (value, 0),
+                    None => (key_value.value.as_str(), key_value.line_cnt)
+                };
+                let value = self.settings.interpolate(&self.section.name, &key_value.key, line, raw_value)?;
+                seed.deserialize(ValueDeserializer {
+                    settings: self.settings,
+                    section: &self.section.name,
+                    key: &key_value.key,
+                    line,
+                    value: &value
+                })
+            },
+            None => Err(de::Error::custom("key value requested before its key"))
+        }
+    }
+}
+
+// Deserializer of the elements of an array, a sequence of values
+struct ArrayDeserializer<'a> {
+    settings: &'a Settings,
+    section: &'a str,
+    key: &'a str,
+    elements: Vec<(String, usize)>,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ArrayDeserializer<'a> {
+    type Error = SettingsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_seq(ElementsAccess {
+            settings: self.settings,
+            section: self.section,
+            key: self.key,
+            elements: self.elements.into_iter()
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+// Access to the elements of an array as sequence elements
+struct ElementsAccess<'a> {
+    settings: &'a Settings,
+    section: &'a str,
+    key: &'a str,
+    elements: IntoIter<(String, usize)>,
+}
+
+impl<'de, 'a> SeqAccess<'de> for ElementsAccess<'a> {
+    type Error = SettingsError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, SettingsError> {
+        match self.elements.next() {
+            Some((value, line)) => seed.deserialize(ValueDeserializer {
+                settings: self.settings,
+                section: self.section,
+                key: self.key,
+                line,
+                value: &value
+            }).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
+
+// Deserializer of a single value, parsed with the FromStr trait
+struct ValueDeserializer<'a> {
+    settings: &'a Settings,
+    section: &'a str,
+    key: &'a str,
+    line: usize,
+    value: &'a str,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    // Parses the value, the error is the one returned by Settings::get
+    fn parse<T: FromStr>(&self) -> Result<T, SettingsError> where <T as FromStr>::Err: Debug {
+        self.value.parse::<T>()
+            .map_err(|error| self.settings.parsing_error(self.section, self.key, self.line, format!("{:#?}", error)))
+    }
+}
+
+// Generates the deserialize methods of the types parsed with FromStr
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = SettingsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_str(self.value)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    // an empty value is None
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        if self.value.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // unit variants are written with their name
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, SettingsError> {
+        let variant: StrDeserializer<SettingsError> = self.value.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SettingsError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf seq tuple tuple_struct map struct identifier
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+
+use crate::COMMENT_TAG;
+
+// Crate private module holding the lossless model of a settings file.
+// Every line read by Settings::load_private is kept as it is
+// (comments, blank lines, spaces around the assign tag, inline comments
+// and line endings) so that saving an unchanged Settings writes back
+// exactly the same bytes and a modified value only replaces its own text.
+
+const DEFAULT_EOL: &str = "\n";
+// Indentation of the continuation lines of the multi-line values written by Settings
+pub(crate) const CONTINUATION_INDENT: &str = "    ";
+// Characters that can enclose a value
+pub(crate) const QUOTES: [char; 2] = ['"', '\''];
+
+// The tokens of a key/value line
+// 'id' links the line to its KeyValuePair (see KeyValuePair::id)
+// 'prefix' holds indentation, key, assign tag and the spaces around it
+// 'raw_value' is the value text as written in the file, the one of a multi-line
+// value spans several physical lines and holds their line endings
+// 'value' is the value as parsed from 'raw_value', it is compared with
+// the KeyValuePair value to know if 'raw_value' has to be rewritten
+// 'suffix' holds the spaces after the value and the inline comment
+// 'continuation' is the line ending and indentation written between the lines
+// of a value containing line feeds, None if such a value has to be quoted
+#[derive(Clone)]
+pub(crate) struct KeyValueLine {
+    pub(crate) id: usize,
+    pub(crate) prefix: String,
+    pub(crate) raw_value: String,
+    pub(crate) value: String,
+    pub(crate) suffix: String,
+    pub(crate) continuation: Option<String>,
+}
+
+impl KeyValueLine {
+    // Associated function to create the tokens of a key/value line
+    // inserted by Settings, not read from a file
+    //  * `id` -  The KeyValuePair id
+    //  * `key` -  A string slice that holds the key
+    //  * `value` -  A string slice that holds the value
+    //  * `continuation` -  The text written between the lines of a multi-line value
+    pub(crate) fn new(id: usize, key: &str, value: &str, continuation: Option<String>) -> Self {
+        Self {
+            id,
+            prefix: format!("{} {} ", key, crate::ASSIGN_TAG),
+            raw_value: encode_value(value, continuation.as_deref()),
+            value: value.to_string(),
+            suffix: String::new(),
+            continuation,
+        }
+    }
+
+    // Sets the current value of the key, the original text of the value
+    // is kept if the value did not change
+    fn set_value(&mut self, value: &str) {
+        if value == self.value {
+            return;
+        }
+        if self.raw_value.is_empty() {
+            if !self.prefix.ends_with(char::is_whitespace) {
+                self.prefix.push(' ');
+            }
+            if !self.suffix.is_empty() && !self.suffix.starts_with(char::is_whitespace) {
+                self.suffix.insert(0, ' ');
+            }
+        }
+        // a quoted value stays quoted
+        self.raw_value = if self.raw_value.starts_with(QUOTES) {
+            quote_value(value)
+        } else {
+            encode_value(value, self.continuation.as_deref())
+        };
+        self.value = value.to_string();
+    }
+
+    // Returns the number of physical lines of the key/value line
+    pub(crate) fn lines_count(&self) -> usize {
+        self.raw_value.matches('\n').count() + 1
+    }
+
+    // Returns the line text without line ending
+    fn text(&self) -> String {
+        format!("{}{}{}", self.prefix, self.raw_value, self.suffix)
+    }
+
+    // Returns the line text without line ending using 'value' as
+    // current value of the key
+    fn render(&self, value: &str) -> String {
+        if value == self.value {
+            return self.text();
+        }
+        let mut key_value_line = self.clone();
+        key_value_line.set_value(value);
+        key_value_line.text()
+    }
+}
+
+// Crate private enumeration of the kinds of line of a settings file
+pub(crate) enum LineKind {
+    Trivia(String),  // Empty or comment line, the String is the line text
+    SectionTag(String, String),  // Section tag line, the name of the section and the line text
+    Directive(String),  // Include directive line, the String is the line text
+    KeyValue(KeyValueLine)  // Key/value line
+}
+
+// A settings file line, 'eol' is the line ending as read from the file
+// ("\n", "\r\n" or an empty string for the last line without line ending)
+pub(crate) struct Line {
+    pub(crate) kind: LineKind,
+    pub(crate) eol: String,
+}
+
+impl Line {
+    // Returns true if the line is a comment line
+    pub(crate) fn is_comment(&self) -> bool {
+        match &self.kind {
+            LineKind::Trivia(text) => text.trim_start().starts_with(COMMENT_TAG),
+            _ => false
+        }
+    }
+
+    // Returns true if the line is an empty line
+    pub(crate) fn is_empty(&self) -> bool {
+        match &self.kind {
+            LineKind::Trivia(text) => text.trim().is_empty(),
+            _ => false
+        }
+    }
+
+    // Returns true if the line is an include directive line
+    pub(crate) fn is_directive(&self) -> bool {
+        matches!(self.kind, LineKind::Directive(_))
+    }
+
+    // Returns the number of physical lines of the line,
+    // more than one for a key/value line holding a multi-line value
+    pub(crate) fn lines_count(&self) -> usize {
+        match &self.kind {
+            LineKind::KeyValue(key_value_line) => key_value_line.lines_count(),
+            _ => 1
+        }
+    }
+
+    // Returns the KeyValuePair id if the line is a key/value line
+    pub(crate) fn key_value_id(&self) -> Option<usize> {
+        match &self.kind {
+            LineKind::KeyValue(key_value_line) => Some(key_value_line.id),
+            _ => None
+        }
+    }
+
+    // Returns the section name if the line is a section tag line
+    pub(crate) fn section_name(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::SectionTag(name, _) => Some(name),
+            _ => None
+        }
+    }
+}
+
+// The lossless model of a settings file: the sequence of its lines
+#[derive(Default)]
+pub(crate) struct Document {
+    pub(crate) lines: Vec<Line>,
+}
+
+impl Document {
+    // Appends a line read from the settings file
+    pub(crate) fn push(&mut self, kind: LineKind, eol: &str) {
+        self.lines.push(Line { kind, eol: eol.to_string() });
+    }
+
+    // Returns the line ending used by the file, the one of the first line
+    // or "\n" if no line has a line ending
+    pub(crate) fn eol(&self) -> String {
+        self.lines.iter().map(|line| line.eol.as_str())
+            .find(|eol| !eol.is_empty())
+            .unwrap_or(DEFAULT_EOL).to_string()
+    }
+
+    // Inserts a line at 'index' using the document line ending,
+    // if the line is appended after a last line without line ending
+    // the new line takes the role of last line
+    pub(crate) fn insert(&mut self, index: usize, kind: LineKind) {
+        let eol = self.eol();
+        if index == self.lines.len() {
+            if let Some(last_line) = self.lines.last_mut() {
+                if last_line.eol.is_empty() {
+                    last_line.eol = eol;
+                    self.lines.push(Line { kind, eol: String::new() });
+                    return;
+                }
+            }
+        }
+        self.lines.insert(index, Line { kind, eol });
+    }
+
+    // Removes the line at 'index' and, if 'comments' is true,
+    // the comment lines right above it
+    pub(crate) fn remove(&mut self, index: usize, comments: bool) {
+        let mut removed = vec![false; self.lines.len()];
+        removed[index] = true;
+        if comments {
+            self.mark_comments_above(index, &mut removed);
+        }
+        self.remove_lines(&removed);
+    }
+
+    // Marks as removed the comment lines right above the line at 'index'
+    pub(crate) fn mark_comments_above(&self, index: usize, removed: &mut [bool]) {
+        let mut comment_idx = index;
+        while comment_idx > 0 && self.lines[comment_idx - 1].is_comment() {
+            comment_idx -= 1;
+            removed[comment_idx] = true;
+        }
+    }
+
+    // Removes the lines marked as removed, if the last line is removed
+    // the new last line loses its line ending as the removed one had not
+    pub(crate) fn remove_lines(&mut self, removed: &[bool]) {
+        let last_eol_empty = self.lines.last().is_some_and(|line| line.eol.is_empty());
+        let last_removed = removed.last().copied().unwrap_or(false);
+        let mut kept = removed.iter().map(|removed| !removed);
+        self.lines.retain(|_| kept.next().unwrap_or(true));
+        if last_eol_empty && last_removed {
+            if let Some(last_line) = self.lines.last_mut() {
+                last_line.eol.clear();
+            }
+        }
+    }
+
+    // Returns the position of the line of a key/value pair
+    pub(crate) fn position(&self, id: usize) -> Option<usize> {
+        self.lines.iter().position(|line| line.key_value_id() == Some(id))
+    }
+
+    // Returns the settings file text, 'values' maps KeyValuePair ids
+    // to their current values
+    pub(crate) fn text(&self, values: &HashMap<usize, &String>) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            match &line.kind {
+                LineKind::Trivia(line_text) | LineKind::SectionTag(_, line_text) | LineKind::Directive(line_text) => {
+                    text.push_str(line_text);
+                },
+                LineKind::KeyValue(key_value_line) => {
+                    match values.get(&key_value_line.id) {
+                        Some(value) => text.push_str(&key_value_line.render(value)),
+                        None => text.push_str(&key_value_line.text())
+                    }
+                }
+            }
+            text.push_str(&line.eol);
+        }
+        text
+    }
+
+    // Makes the current values of the key/value pairs the original ones,
+    // called once the document text has been saved
+    pub(crate) fn commit(&mut self, values: &HashMap<usize, &String>) {
+        for line in &mut self.lines {
+            if let LineKind::KeyValue(key_value_line) = &mut line.kind {
+                if let Some(value) = values.get(&key_value_line.id) {
+                    key_value_line.set_value(value);
+                }
+            }
+        }
+    }
+}
+
+// Returns the text used to write a value in a settings file,
+// the value is quoted if it can not be read back unchanged without quotes:
+// leading or trailing spaces, comment tags, leading quotes, control characters
+// or a trailing backslash that would continue the value on the next line
+// (see Settings::with_multi_line_values)
+// With a 'continuation' a value containing line feeds is written on indented
+// continuation lines if every line can be read back unchanged
+pub(crate) fn encode_value(value: &str, continuation: Option<&str>) -> String {
+    if let Some(continuation) = continuation {
+        if value.contains('\n') && can_indent(value) {
+            return value.split('\n').collect::<Vec<&str>>().join(continuation);
+        }
+    }
+    let needs_quotes = value != value.trim() || value.starts_with(QUOTES)
+        || value.contains(COMMENT_TAG) || value.chars().any(char::is_control) || value.ends_with('\\');
+    if needs_quotes {
+        quote_value(value)
+    } else {
+        value.to_string()
+    }
+}
+
+// Returns true if the lines of a multi-line value can be written on indented
+// continuation lines: the first line as an unquoted value, the following ones
+// not empty, without leading or trailing spaces and control characters,
+// no line ends with a backslash that would continue it
+fn can_indent(value: &str) -> bool {
+    let mut lines = value.split('\n');
+    let first_line = lines.next().unwrap_or_default();
+    first_line == encode_value(first_line, None) && !first_line.ends_with('\\')
+        && lines.all(|line| !line.is_empty() && line == line.trim()
+            && !line.chars().any(char::is_control) && !line.ends_with('\\'))
+}
+
+// Returns the value enclosed in double quotes with its quotes,
+// backslashes and control characters escaped
+fn quote_value(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c)
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Decodes the quoted value at the start of 'text', enclosed in double or single quotes,
+// the escape sequences are \" \' \\ \n \t \r and \uXXXX (UTF-16 surrogate pairs included)
+// Returns the value and the length of the quoted text, quotes included, or None
+// if the closing quote is missing or an escape sequence is not valid
+pub(crate) fn decode_quoted(text: &str) -> Option<(String, usize)> {
+    let mut chars = text.char_indices();
+    let (_, quote) = chars.next().filter(|(_, quote)| QUOTES.contains(quote))?;
+    let mut value = String::new();
+    while let Some((index, c)) = chars.next() {
+        if c == quote {
+            return Some((value, index + c.len_utf8()));
+        }
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()?.1 {
+            escaped @ ('"' | '\'' | '\\') => value.push(escaped),
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            'u' => {
+                let code = decode_hex4(&mut chars)?;
+                let code = if (0xD800..0xDC00).contains(&code) {
+                    // high surrogate, the low one must follow
+                    if chars.next()?.1 != '\\' || chars.next()?.1 != 'u' {
+                        return None;
+                    }
+                    let low = decode_hex4(&mut chars)?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return None;
+                    }
+                    0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    code
+                };
+                value.push(char::from_u32(code)?);
+            },
+            _ => return None
+        }
+    }
+    None
+}
+
+// Decodes the 4 hexadecimal digits of a \uXXXX escape sequence
+fn decode_hex4(chars: &mut std::str::CharIndices) -> Option<u32> {
+    let mut code = 0u32;
+    for _ in 0..4 {
+        code = code * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    Some(code)
+}
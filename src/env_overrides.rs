@@ -0,0 +1,127 @@
+use std::env;
+
+use crate::Settings;
+
+// Module overriding the values of Settings with environment variables:
+// the variable PREFIX<separator>SECTION<separator>KEY overrides the key of the section
+// when values are read, section and key names are matched ignoring case.
+// Overriding values are kept apart from the sections, so they are never saved.
+
+/// Environment variables overriding the values read from Settings, see Settings::with_env_overrides
+/// A variable named prefix, separator, section, separator and key (i.g. APP__DATABASE__PORT with
+/// prefix "APP" and separator "__") overrides the key of the section, section and key names
+/// are matched ignoring case; the key can contain the separator, the section can not
+/// # Examples
+/// ```
+/// use rssettings::EnvOverrides;
+///
+/// fn main() {
+///     let variables = vec![("APP__DATABASE__PORT".to_string(), "5433".to_string())];
+///     let env_overrides = EnvOverrides::from_variables("APP", "__", variables);
+///     assert_eq!(Some("5433"), env_overrides.value("DATABASE", "port"));
+///     assert_eq!(Some("APP__DATABASE__PORT"), env_overrides.variable("Database", "Port"));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EnvOverrides {
+    prefix: String,
+    separator: String,
+    overrides: Vec<EnvOverride>,
+}
+
+// A variable overriding a key
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EnvOverride {
+    variable: String,
+    section: String,
+    key: String,
+    value: String,
+}
+
+impl EnvOverrides {
+    /// Associated function to create the overrides of the process environment variables,
+    /// the variables are read once when it is called
+    ///  * `prefix` -  A string slice that holds the prefix of the variable names, none if empty
+    ///  * `separator` -  A string slice that holds the separator of prefix, section and key
+    ///
+    pub fn new(prefix: &str, separator: &str) -> Self {
+        Self::from_variables(prefix, separator, env::vars())
+    }
+
+    /// Associated function to create the overrides of a list of variables (i.g. in tests)
+    /// Variables not starting with the prefix or without section and key are ignored,
+    /// an empty separator ignores all of them
+    ///  * `prefix` -  A string slice that holds the prefix of the variable names, none if empty
+    ///  * `separator` -  A string slice that holds the separator of prefix, section and key
+    ///  * `variables` -  The names and values of the variables
+    ///
+    pub fn from_variables<I: IntoIterator<Item = (String, String)>>(prefix: &str, separator: &str, variables: I) -> Self {
+        let overrides = if separator.is_empty() {
+            vec![]
+        } else {
+            variables.into_iter().filter_map(|(variable, value)| {
+                let name = if prefix.is_empty() {
+                    variable.as_str()
+                } else {
+                    variable.strip_prefix(prefix)?.strip_prefix(separator)?
+                };
+                let (section, key) = name.split_once(separator)?;
+                if section.is_empty() || key.is_empty() {
+                    return None;
+                }
+                let (section, key) = (section.to_string(), key.to_string());
+                Some(EnvOverride { variable, section, key, value })
+            }).collect()
+        };
+        Self { prefix: prefix.to_string(), separator: separator.to_string(), overrides }
+    }
+
+    /// Returns the prefix of the variable names
+    ///  * `self` -  An immutable reference to EnvOverrides struct
+    ///
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the separator of prefix, section and key
+    ///  * `self` -  An immutable reference to EnvOverrides struct
+    ///
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    /// Returns the value overriding a key, None if no variable overrides it
+    ///  * `self` -  An immutable reference to EnvOverrides struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///
+    pub fn value(&self, section_name: &str, key: &str) -> Option<&str> {
+        self.find(section_name, key).map(|env_override| env_override.value.as_str())
+    }
+
+    /// Returns the name of the variable overriding a key, None if no variable overrides it
+    ///  * `self` -  An immutable reference to EnvOverrides struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///
+    pub fn variable(&self, section_name: &str, key: &str) -> Option<&str> {
+        self.find(section_name, key).map(|env_override| env_override.variable.as_str())
+    }
+
+    // Returns the override of a key, the last one if more variables override it
+    fn find(&self, section_name: &str, key: &str) -> Option<&EnvOverride> {
+        self.overrides.iter().rev().find(|env_override| {
+            env_override.section.eq_ignore_ascii_case(section_name) && env_override.key.eq_ignore_ascii_case(key)
+        })
+    }
+}
+
+impl Settings {
+    // Returns the value of the environment variable overriding a key, see Settings::with_env_overrides
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section
+    pub(crate) fn env_override(&self, section_name: &str, key: &str) -> Option<&str> {
+        self.env_overrides.value(section_name, key)
+    }
+}
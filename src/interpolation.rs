@@ -0,0 +1,194 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::env;
+
+use crate::{Settings, SettingsError, AT_LINE_MESSAGE_IDX, INTERPOLATION_CYCLE_MESSAGE_IDX,
+    UNDEFINED_REFERENCE_MESSAGE_IDX, UNDEFINED_VARIABLE_MESSAGE_IDX};
+
+// Module interpolating the references found in values when they are read
+// (see Settings::get_value): ${NAME} and ${NAME:-default} are replaced with
+// the value of an environment variable, ${key} and ${SECTION:key} with the
+// interpolated value of a key of the same section or of another section,
+// $$ is a single $.
+// Values are stored and saved with their references, never interpolated.
+
+const REFERENCE_START: &str = "${";
+const REFERENCE_END: char = '}';
+const DEFAULT_SEPARATOR: &str = ":-";
+const SECTION_SEPARATOR: char = ':';
+const ESCAPED_DOLLAR: &str = "$$";
+
+/// Enumeration of the sources of the environment variables interpolated
+/// in the values returned by Settings, see Settings::with_env_interpolation
+/// 'Disabled' values are returned verbatim, it is the default
+/// 'Process' variables are read from the process environment
+/// 'Map' variables are read from a map given by the caller (i.g. in tests)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum EnvInterpolation {
+    #[default]
+    Disabled,
+    Process,
+    Map(HashMap<String, String>)
+}
+
+impl EnvInterpolation {
+    // Returns the value of a variable, None if it is not defined
+    //  * `name` -  A string slice that holds the variable name
+    fn variable(&self, name: &str) -> Option<String> {
+        match self {
+            EnvInterpolation::Disabled => None,
+            EnvInterpolation::Process => env::var(name).ok(),
+            EnvInterpolation::Map(variables) => variables.get(name).cloned()
+        }
+    }
+}
+
+impl Settings {
+    // Returns the value of a key with its references interpolated,
+    // the value itself if interpolation is disabled or it has no references
+    // Returns std::result::Result::Err(SettingsError::UndefinedVariable) if
+    // a referenced variable is not defined and has no default,
+    // std::result::Result::Err(SettingsError::UndefinedReference) if a referenced key
+    // does not exist, std::result::Result::Err(SettingsError::InterpolationCycle)
+    // if the references lead back to an interpolated key
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section
+    //  * `line` -  The settings file line of the key, used in errors
+    //  * `value` -  A string slice that holds the value to interpolate
+    pub(crate) fn interpolate<'a>(&self, section_name: &str, key: &str, line: usize, value: &'a str) -> Result<Cow<'a, str>, SettingsError> {
+        if (self.env_interpolation == EnvInterpolation::Disabled && !self.key_interpolation) || !value.contains('$') {
+            return Ok(Cow::Borrowed(value));
+        }
+        let mut chain = vec![(section_name.to_string(), key.to_string(), line)];
+        self.interpolate_value(section_name, key, line, value, &mut chain).map(Cow::Owned)
+    }
+
+    // Returns a value with its references interpolated
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section
+    //  * `line` -  The settings file line of the key, used in errors
+    //  * `value` -  A string slice that holds the value to interpolate
+    //  * `chain` -  The section, key and line of the keys being interpolated,
+    //     the outer one first, used to detect reference cycles
+    fn interpolate_value(&self, section_name: &str, key: &str, line: usize, value: &str, chain: &mut Vec<(String, String, usize)>) -> Result<String, SettingsError> {
+        let mut interpolated = String::new();
+        let mut rest = value;
+        while let Some(index) = rest.find('$') {
+            interpolated.push_str(&rest[..index]);
+            rest = &rest[index..];
+            if let Some(after) = rest.strip_prefix(ESCAPED_DOLLAR) {
+                interpolated.push('$');
+                rest = after;
+                continue;
+            }
+            // a reference without end is not a reference
+            let reference_end = rest.find(REFERENCE_END).filter(|_| rest.starts_with(REFERENCE_START));
+            match reference_end {
+                Some(end) => {
+                    let reference = &rest[REFERENCE_START.len()..end];
+                    interpolated.push_str(&self.resolve_reference(section_name, key, line, reference, chain)?);
+                    rest = &rest[end + 1..];
+                },
+                None => {
+                    interpolated.push('$');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        interpolated.push_str(rest);
+        Ok(interpolated)
+    }
+
+    // Returns the value of a reference: the interpolated value of the referenced key
+    // if key interpolation is enabled and the key exists, the value of the
+    // environment variable otherwise
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section
+    //  * `line` -  The settings file line of the key, used in errors
+    //  * `reference` -  The reference text between ${ and }
+    //  * `chain` -  The keys being interpolated, see Settings::interpolate_value
+    fn resolve_reference(&self, section_name: &str, key: &str, line: usize, reference: &str, chain: &mut Vec<(String, String, usize)>) -> Result<String, SettingsError> {
+        if !self.key_interpolation {
+            return self.resolve_variable(section_name, key, line, reference);
+        }
+        // ${NAME:-default} is a variable with default value, not a section reference
+        let cross_reference = reference.split_once(SECTION_SEPARATOR)
+            .filter(|(_, referenced_key)| !referenced_key.starts_with('-'));
+        let (referenced_section, referenced_key) = cross_reference.unwrap_or((section_name, reference));
+        let key_value = self.get_section(referenced_section).and_then(|section| section.get_key_value(referenced_key))
+            .map(|key_value| (key_value.value.as_str(), key_value.line_cnt));
+        // overrides win over the file values (see Settings::apply_overrides)
+        let key_value = self.value_override(referenced_section, referenced_key).map(|value| (value, 0)).or(key_value);
+        let (value, value_line) = match key_value {
+            Some(key_value) => key_value,
+            None if cross_reference.is_none() && (self.env_interpolation != EnvInterpolation::Disabled
+                || reference.contains(DEFAULT_SEPARATOR)) => {
+                return self.resolve_variable(section_name, key, line, reference);
+            },
+            None => return Err(self.undefined_reference_error(section_name, key, line, reference))
+        };
+        let cycle = chain.iter().any(|(chain_section, chain_key, _)| {
+            self.name_matching.matches(chain_section, referenced_section) && self.name_matching.matches(chain_key, referenced_key)
+        });
+        chain.push((referenced_section.to_string(), referenced_key.to_string(), value_line));
+        if cycle {
+            return Err(self.interpolation_cycle_error(chain.clone()));
+        }
+        let value = self.interpolate_value(referenced_section, referenced_key, value_line, value, chain)?;
+        chain.pop();
+        Ok(value)
+    }
+
+    // Returns the value of the variable of a reference, the default value if
+    // the variable is not defined or empty
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section
+    //  * `line` -  The settings file line of the key, used in errors
+    //  * `reference` -  The reference text between ${ and }
+    fn resolve_variable(&self, section_name: &str, key: &str, line: usize, reference: &str) -> Result<String, SettingsError> {
+        let (name, default_value) = match reference.split_once(DEFAULT_SEPARATOR) {
+            Some((name, default_value)) => (name, Some(default_value)),
+            None => (reference, None)
+        };
+        match (self.env_interpolation.variable(name), default_value) {
+            (Some(value), Some(default_value)) if value.is_empty() => Ok(default_value.to_string()),
+            (Some(value), _) => Ok(value),
+            (None, Some(default_value)) => Ok(default_value.to_string()),
+            (None, None) => Err(self.undefined_variable_error(section_name, key, line, name))
+        }
+    }
+
+    // Returns the error of a reference to a missing key
+    fn undefined_reference_error(&self, section_name: &str, key: &str, line: usize, reference: &str) -> SettingsError {
+        let section = section_name.to_string();
+        let key = key.to_string();
+        let reference = reference.to_string();
+        let message = self.format_message(UNDEFINED_REFERENCE_MESSAGE_IDX, vec![&section, &key, &reference]);
+        SettingsError::UndefinedReference { section, key, line, reference, message }
+    }
+
+    // Returns the error of a reference cycle, the first key of the chain
+    // is the interpolated one and the last one closes the cycle
+    fn interpolation_cycle_error(&self, chain: Vec<(String, String, usize)>) -> SettingsError {
+        let (section, key, _) = chain[0].clone();
+        let chain_text = chain.iter().map(|(chain_section, chain_key, chain_line)| {
+            let reference = format!("{}{}{}", chain_section, SECTION_SEPARATOR, chain_key);
+            self.format_message(AT_LINE_MESSAGE_IDX, vec![&reference, &chain_line.to_string()])
+        }).collect::<Vec<String>>().join(" -> ");
+        let message = self.format_message(INTERPOLATION_CYCLE_MESSAGE_IDX, vec![&section, &key, &chain_text]);
+        SettingsError::InterpolationCycle { section, key, chain, message }
+    }
+
+    // Returns the error of a reference to an undefined variable
+    fn undefined_variable_error(&self, section_name: &str, key: &str, line: usize, name: &str) -> SettingsError {
+        let section = section_name.to_string();
+        let key = key.to_string();
+        let variable = name.to_string();
+        let message = self.format_message(UNDEFINED_VARIABLE_MESSAGE_IDX, vec![&section, &key, &variable]);
+        SettingsError::UndefinedVariable { section, key, line, variable, message }
+    }
+}
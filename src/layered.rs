@@ -0,0 +1,268 @@
+use std::fmt::{Debug, Display};
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{DropPolicy, Settings, SettingsError, SettingsValue, INVALID_LAYER_MESSAGE_IDX, SETTINGS_MESSAGES};
+
+// Module composing several Settings in one view: layers are added from the
+// lowest precedence (i.g. built-in defaults) to the highest one (i.g. local
+// overrides) and every key is read from the highest layer holding it,
+// values are written in a layer chosen by the caller.
+
+/// Settings composed of layers with precedence (i.g. built-in defaults < /etc < user < local override)
+/// Layers are added from the lowest precedence to the highest one, a key is read from
+/// the highest layer holding it (see LayeredSettings::key_layer) and its value is
+/// interpolated by that layer; values are written in a writable layer chosen by the caller
+/// # Examples
+/// ```
+/// use rssettings::{LayeredSettings, Settings};
+///
+/// fn main() {
+///     let defaults: Settings = "[LOG]\nenabled = false\nlevel = 1\n".parse().unwrap();
+///     let user: Settings = "[LOG]\nlevel = 3\n".parse().unwrap();
+///     let mut settings = LayeredSettings::new()
+///         .with_layer("defaults", defaults, false)
+///         .with_layer("user", user, true);
+///     assert_eq!(3, settings.get("LOG", "level", 0).value);
+///     assert_eq!(Some("defaults"), settings.key_layer("LOG", "enabled"));
+///     settings.set("user", "LOG", "enabled", true).unwrap();
+///     assert_eq!(Some("user"), settings.key_layer("LOG", "enabled"));
+///     assert!(settings.set("defaults", "LOG", "level", 5).is_err());
+/// }
+/// ```
+#[derive(Default)]
+pub struct LayeredSettings {
+    layers: Vec<Layer>,
+}
+
+// A layer of LayeredSettings
+struct Layer {
+    name: String,
+    settings: Settings,
+    writable: bool,
+}
+
+impl LayeredSettings {
+    /// Associated function to create a LayeredSettings without layers
+    ///
+    pub fn new() -> Self {
+        Self { layers: vec![] }
+    }
+
+    /// Adds a layer with a precedence higher than the ones already added
+    /// Returns the LayeredSettings with the new layer, see LayeredSettings::add_layer
+    ///  * `self` -  The LayeredSettings struct
+    ///  * `name` -  A string slice that holds the name of the layer
+    ///  * `settings` -  The Settings of the layer
+    ///  * `writable` -  If true values can be written in the layer by LayeredSettings::set
+    ///
+    pub fn with_layer(mut self, name: &str, settings: Settings, writable: bool) -> Self {
+        self.add_layer(name, settings, writable);
+        self
+    }
+
+    /// Adds a layer with a precedence higher than the ones already added,
+    /// a layer with the same name is replaced keeping its precedence
+    /// A layer not writable is never saved when it is dropped, its drop policy
+    /// becomes DropPolicy::Never (see Settings::with_drop_policy)
+    ///  * `self` -  A mutable reference to LayeredSettings struct
+    ///  * `name` -  A string slice that holds the name of the layer
+    ///  * `settings` -  The Settings of the layer
+    ///  * `writable` -  If true values can be written in the layer by LayeredSettings::set
+    ///
+    pub fn add_layer(&mut self, name: &str, mut settings: Settings, writable: bool) {
+        if !writable {
+            settings.drop_policy = DropPolicy::Never;
+        }
+        let layer = Layer { name: name.to_string(), settings, writable };
+        match self.layers.iter_mut().find(|layer| layer.name == name) {
+            Some(existing) => *existing = layer,
+            None => self.layers.push(layer)
+        }
+    }
+
+    /// Loads a settings file in a new layer with a precedence higher than the ones already added,
+    /// a missing file is an empty layer and it is created by LayeredSettings::save
+    /// when values are written in it
+    /// Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError)
+    /// with the error of Settings::load
+    /// # Examples
+    /// ```
+    /// use rssettings::LayeredSettings;
+    ///
+    /// fn main() {
+    ///     let mut settings = LayeredSettings::new();
+    ///     settings.add_file_layer("system", "test_files/settings.ini", false).unwrap();
+    ///     settings.add_file_layer("user", "test_files/goofy.ini", true).unwrap();
+    ///     assert_eq!(true, settings.get("GLOBAL", "bool_value", false).value);
+    /// }
+    /// ```
+    ///  * `self` -  A mutable reference to LayeredSettings struct
+    ///  * `name` -  A string slice that holds the name of the layer
+    ///  * `path` -  A Path as reference to the settings file to load
+    ///  * `writable` -  If true values can be written in the layer by LayeredSettings::set
+    ///
+    pub fn add_file_layer<P: AsRef<Path>>(&mut self, name: &str, path: P, writable: bool) -> Result<(), SettingsError> {
+        // a read only layer changed through LayeredSettings::layer_mut is not saved when dropped
+        let drop_policy = if writable { DropPolicy::default() } else { DropPolicy::Never };
+        let mut settings = Settings::new().with_drop_policy(drop_policy);
+        match settings.load(path.as_ref()) {
+            Ok(()) => {},
+            Err(SettingsError::OpeningFile { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                settings.path = path.as_ref().as_os_str().to_str().unwrap_or("").to_string();
+                settings.file = Some(path.as_ref().to_path_buf());
+                settings.initialized = true;
+            },
+            Err(error) => return Err(error)
+        }
+        self.add_layer(name, settings, writable);
+        Ok(())
+    }
+
+    /// Returns the names of the layers from the lowest precedence to the highest one
+    ///  * `self` -  An immutable reference to LayeredSettings struct
+    ///
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers.iter().map(|layer| layer.name.as_str()).collect()
+    }
+
+    /// Returns the Settings of a layer, None if the layer does not exist
+    ///  * `self` -  An immutable reference to LayeredSettings struct
+    ///  * `name` -  A string slice that holds the name of the layer
+    ///
+    pub fn layer(&self, name: &str) -> Option<&Settings> {
+        self.layers.iter().find(|layer| layer.name == name).map(|layer| &layer.settings)
+    }
+
+    /// Returns the mutable Settings of a layer, None if the layer does not exist
+    /// Layers not writable can be changed through it too
+    ///  * `self` -  A mutable reference to LayeredSettings struct
+    ///  * `name` -  A string slice that holds the name of the layer
+    ///
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Settings> {
+        self.layers.iter_mut().find(|layer| layer.name == name).map(|layer| &mut layer.settings)
+    }
+
+    /// Returns the name of the highest layer holding a key, None if no layer holds it
+    ///  * `self` -  An immutable reference to LayeredSettings struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///
+    pub fn key_layer(&self, section_name: &str, key: &str) -> Option<&str> {
+        self.top_layer(section_name, key).map(|layer| layer.name.as_str())
+    }
+
+    /// Returns true if a layer holds the key
+    ///  * `self` -  An immutable reference to LayeredSettings struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///
+    pub fn key_exists(&self, section_name: &str, key: &str) -> bool {
+        self.top_layer(section_name, key).is_some()
+    }
+
+    /// Generic method use to get the value of the highest layer holding a key,
+    /// see Settings::get
+    ///  * `self` -  An immutable reference to LayeredSettings struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `T` -  A default generic value returned in case an error occurs
+    ///
+    pub fn get<T: FromStr + Display>(&self, section_name: &str, key: &str, default_value: T) -> SettingsValue<T> where <T as FromStr>::Err: Debug {
+        match self.get_value(section_name, key) {
+            Ok(value) => SettingsValue { value, error: None },
+            Err(error) => SettingsValue { value: default_value, error: Some(error) }
+        }
+    }
+
+    /// Generic method use to get the value of the highest layer holding a key
+    /// without a default value, see Settings::get_value
+    /// Returns std::result::Result::Err(SettingsError::SectionNotFound) or
+    /// std::result::Result::Err(SettingsError::KeyNotFound) if no layer holds the key
+    ///  * `self` -  An immutable reference to LayeredSettings struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///
+    pub fn get_value<T: FromStr>(&self, section_name: &str, key: &str) -> Result<T, SettingsError> where <T as FromStr>::Err: Debug {
+        if let Some(layer) = self.top_layer(section_name, key) {
+            return layer.settings.get_value(section_name, key);
+        }
+        // the error tells if the section exists in some layer
+        match self.layers.iter().rev().find(|layer| layer.settings.section_exists(section_name)).or(self.layers.last()) {
+            Some(layer) => layer.settings.get_value(section_name, key),
+            None => Settings::new().get_value(section_name, key)
+        }
+    }
+
+    /// Writes a value in a writable layer, missing section and key are inserted
+    /// (see Settings::set_or_insert)
+    /// Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError::InvalidLayer)
+    /// if the layer does not exist or it is not writable
+    ///  * `self` -  A mutable reference to LayeredSettings struct
+    ///  * `layer_name` -  A string slice that holds the name of the layer
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `value` -  The value to write
+    ///
+    pub fn set<T: Display>(&mut self, layer_name: &str, section_name: &str, key: &str, value: T) -> Result<(), SettingsError> {
+        self.writable_layer(layer_name)?.set_or_insert(section_name, key, value)
+    }
+
+    /// Removes a key from a writable layer, the value of a lower layer
+    /// holding the key becomes visible (see Settings::remove_key)
+    /// Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError::InvalidLayer)
+    /// if the layer does not exist or it is not writable, the error of Settings::remove_key otherwise
+    ///  * `self` -  A mutable reference to LayeredSettings struct
+    ///  * `layer_name` -  A string slice that holds the name of the layer
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///
+    pub fn remove_key(&mut self, layer_name: &str, section_name: &str, key: &str) -> Result<(), SettingsError> {
+        self.writable_layer(layer_name)?.remove_key(section_name, key, true)
+    }
+
+    /// Saves the changed writable layers in their settings files, layers
+    /// loaded from a reader or a string are not saved (see Settings::save)
+    /// Returns std::result::Result::Ok(()) or the error of the first layer that can not be saved
+    ///  * `self` -  A mutable reference to LayeredSettings struct
+    ///
+    pub fn save(&mut self) -> Result<(), SettingsError> {
+        for layer in self.layers.iter_mut().filter(|layer| layer.writable && layer.settings.is_dirty()) {
+            layer.settings.save()?;
+        }
+        Ok(())
+    }
+
+    // Returns the highest layer holding a key
+    //  * `self` -  An immutable reference to LayeredSettings struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section
+    fn top_layer(&self, section_name: &str, key: &str) -> Option<&Layer> {
+        self.layers.iter().rev().find(|layer| {
+            layer.settings.get_section(section_name).and_then(|section| section.get_key_value(key)).is_some()
+        })
+    }
+
+    // Returns the Settings of a writable layer or the SettingsError::InvalidLayer error
+    //  * `self` -  A mutable reference to LayeredSettings struct
+    //  * `layer_name` -  A string slice that holds the name of the layer
+    fn writable_layer(&mut self, layer_name: &str) -> Result<&mut Settings, SettingsError> {
+        let layer = self.layers.iter().position(|layer| layer.name == layer_name && layer.writable);
+        match layer {
+            Some(index) => Ok(&mut self.layers[index].settings),
+            None => Err(self.invalid_layer_error(layer_name))
+        }
+    }
+
+    // Returns the error of a missing or read only layer, the message uses
+    // the messages table of the highest layer or the english one without layers
+    fn invalid_layer_error(&self, layer_name: &str) -> SettingsError {
+        let layer = layer_name.to_string();
+        let message = match self.layers.last() {
+            Some(top) => top.settings.format_message(INVALID_LAYER_MESSAGE_IDX, vec![&layer]),
+            None => SETTINGS_MESSAGES[INVALID_LAYER_MESSAGE_IDX].replacen("{}", &layer, 1)
+        };
+        SettingsError::InvalidLayer { layer, message }
+    }
+}
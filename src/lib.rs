@@ -1,11 +1,43 @@
+mod document;
+mod watcher;
+mod interpolation;
+mod layered;
+mod env_overrides;
+mod arg_overrides;
+mod nested;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+mod ser;
+#[cfg(feature = "schema")]
+mod schema;
+
 use std::result::Result as StdResult;
-use std::io::{Result as IoResult, Write};
+use std::io::{Result as IoResult, Read, Write};
 use std::io::{self, BufRead};
-use std::fs::File;
+use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::error::Error;
+use std::fs::{self, File};
 use std::fmt::Display;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::SystemTime;
 use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use document::{Document, KeyValueLine, LineKind, CONTINUATION_INDENT};
+pub use watcher::{SettingChange, SettingsWatcher, WatcherHandle};
+pub use interpolation::EnvInterpolation;
+pub use layered::LayeredSettings;
+pub use env_overrides::EnvOverrides;
+pub use nested::SettingsTree;
+#[cfg(feature = "derive")]
+pub use rssettings_derive::SettingsSection;
+#[cfg(feature = "schema")]
+pub use schema::{KeySchema, Schema, SectionSchema, ValueType};
 
 
 
@@ -13,27 +45,45 @@ const COMMENT_TAG: &str = "#";
 const START_SECTION_TAG: &str = "[";
 const END_SECTION_TAG: &str = "]";
 const ASSIGN_TAG: &str = "=";
+// Suffix of the repeated keys holding the elements of an array (see Settings::with_array_keys)
+const ARRAY_TAG: &str = "[]";
+// Directives loading another settings file or the files of a directory
+// (i.g. "!include local.conf", "!includedir conf.d"), see Settings::load
+const INCLUDE_DIRECTIVE: &str = "!include";
+const INCLUDE_DIR_DIRECTIVE: &str = "!includedir";
 /// GLOBAL_SECTION is a constant that can
 /// be used to retrieve all values that do not have a section
 /// due to the fact that when loading the settings file
 /// a section name is empty or some lines of key/value pairs
 /// were found before the first valid section
 pub const GLOBAL_SECTION: &str = "GLOBAL";
+// Name used in error messages for Settings created by FromStr::from_str
+const STRING_SOURCE_NAME: &str = "<string>";
+// Counter making unique the temporary files of the saves running in the process
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 // A crate privite structure that represents the key/value pair
 // inside a Section structure
 // line_cnt represent the file line where the key & value 
 // has been found during settings file loading (see Settings::load_private)
+// or 0 if the key/value pair has been inserted and not yet saved
+// last_line_cnt is the last file line of a multi-line value, line_cnt otherwise
+// id identifies the key/value pair line inside the settings file document
+// source is the file of the key/value pair: 0 for the settings file, the position
+// in Settings::includes plus one for an included file
 struct KeyValuePair {
     key: String,
     value: String,
     line_cnt: usize,
+    last_line_cnt: usize,
+    id: usize,
+    source: usize,
 }
 
 // Display trait implementation for KetValuePair struct
 impl Display for KeyValuePair {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "key: {}, value: {}\n", self.key, self.value)
+        writeln!(f, "key: {}, value: {}", self.key, self.value)
     }
 }
 
@@ -47,11 +97,15 @@ impl PartialEq for KeyValuePair {
 // KeyValuePair implementation
 impl KeyValuePair {
     // Associated function to create a new KeyValuePair taking ownership of passed arguments
-    fn new(key: String, value: String, line_cnt: usize) -> Self {
+    // `lines` are the first and the last file line of the key/value pair
+    fn new(key: String, value: String, lines: (usize, usize), id: usize, source: usize) -> Self {
         Self {
             key,
             value, 
-            line_cnt
+            line_cnt: lines.0,
+            last_line_cnt: lines.1,
+            id,
+            source
         }
     }
 }
@@ -59,20 +113,25 @@ impl KeyValuePair {
 
 
 // Private crate struct that represents settings file section
+// line_cnt represent the file line where the section tag
+// has been found during settings file loading or 0 if the section
+// has no tag line in the file (GLOBAL section or inserted section not yet saved)
+// name_matching tells how the keys are compared, see Settings::with_name_matching
 struct Section {
     name: String,
-    values: Vec<KeyValuePair>
+    values: Vec<KeyValuePair>,
+    line_cnt: usize,
+    name_matching: NameMatching
 }
 
 // Display trait implementation for Section structure
 impl Display for Section {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}]\n", self.name)?;
-        let mut iter = self.values.iter();
-        while let Some(key_value) = iter.next() {
+        writeln!(f, "[{}]", self.name)?;
+        for key_value in &self.values {
            write!(f, "{}", key_value)? 
         }
-        write!(f, "\n")
+        writeln!(f)
     }    
 }
 
@@ -87,10 +146,14 @@ impl PartialEq for Section {
 impl Section {
     // Associated function to create e new Section
     // * `name` -  A string slice that holds the name of the Section
-    fn new(name: &str) -> Self {
+    // * `line_cnt` -  The file line where the section tag has been found
+    // * `name_matching` -  How the keys of the Section are compared
+    fn new(name: &str, line_cnt: usize, name_matching: NameMatching) -> Self {
         Self {
             name: name.to_string(),
             values: vec![],
+            line_cnt,
+            name_matching
         }
     }
 
@@ -98,35 +161,36 @@ impl Section {
     // if key already exists Result::Err contains
     // the previous line where the duplicated
     // key has been found
-    fn add(&mut self, key: String, value: String, line_cnt: usize) -> StdResult<(), usize> {
-        let mut iter = self.values.iter_mut();
-        while let Some(key_value) = iter.next() {
-            if key_value.key == key {
-                return StdResult::Err(key_value.line_cnt.clone());
-            }
+    fn add(&mut self, key_value: KeyValuePair) -> StdResult<(), usize> {
+        if let Some(previous) = self.get_key_value(&key_value.key) {
+            return StdResult::Err(previous.line_cnt);
         }
-        self.values.push(KeyValuePair::new(key, value, line_cnt));
+        self.push(key_value);
         StdResult::Ok(())
     }
 
+    // Appends a key/value pair without checking if its key already exists,
+    // used for the repeated keys of the arrays
+    fn push(&mut self, key_value: KeyValuePair) {
+        self.values.push(key_value);
+    }
+
     // Resturns if [key] found a reference to key's associated value else Option::None
     fn get(&self, key: &str) -> Option<&String> {
-        let mut iter = self.values.iter();
-        while let Some(key_value) = iter.next() {
-            if key_value.key == key {
-                return Some(&key_value.value);
-            }
-        }
-        None
+        self.get_key_value(key).map(|key_value| &key_value.value)
+    }
+
+    // Resturns if [key] found a reference to its KeyValuePair else Option::None
+    fn get_key_value(&self, key: &str) -> Option<&KeyValuePair> {
+        self.values.iter().find(|key_value| self.name_matching.matches(&key_value.key, key))
     }
 
     // Sets the new associated value of [key]
     // Returns true if [key] has been found 
     // false otherwise.   
     fn set(&mut self, key: &str, value: String) -> bool {
-        let mut iter = self.values.iter_mut();
-        while let Some(key_value) = iter.next() {
-            if key_value.key == key {
+        for key_value in &mut self.values {
+            if self.name_matching.matches(&key_value.key, key) {
                 key_value.value = value;
                 return true;
             }
@@ -137,10 +201,19 @@ impl Section {
     fn unload(&mut self) {
         self.values.clear();
     }
+
+    // Removes [key] from the section
+    // Returns the removed KeyValuePair if [key] has been found
+    // None otherwise
+    fn remove(&mut self, key: &str) -> Option<KeyValuePair> {
+        let index = self.values.iter().position(|key_value| self.name_matching.matches(&key_value.key, key))?;
+        Some(self.values.remove(index))
+    }
 }
 
 
 
+
 const OPENING_FILE_ERROR_MESSAGE_IDX: usize = 0usize;
 const MISSING_START_SECTION_TAG_MESSAGE_IDX: usize = OPENING_FILE_ERROR_MESSAGE_IDX + 1usize;
 const MISSING_END_SECTION_TAG_MESSAGE_IDX: usize = MISSING_START_SECTION_TAG_MESSAGE_IDX + 1usize;
@@ -153,8 +226,28 @@ const PARSING_ERROR_MESSAGE_IDX: usize = KEY_NOT_FOUND_MESSAGE_IDX + 1usize;
 const WRITING_FILE_ERROR_MESSAGE_IDX: usize = PARSING_ERROR_MESSAGE_IDX + 1usize;
 const READING_FILE_ERROR_MESSAGE_IDX: usize = WRITING_FILE_ERROR_MESSAGE_IDX + 1usize;
 const ALREADY_INITIALIZED_MESSAGE_IDX: usize = READING_FILE_ERROR_MESSAGE_IDX + 1usize;
+const INVALID_NAME_MESSAGE_IDX: usize = ALREADY_INITIALIZED_MESSAGE_IDX + 1usize;
+const CONFLICT_MESSAGE_IDX: usize = INVALID_NAME_MESSAGE_IDX + 1usize;
+const SERDE_MESSAGE_IDX: usize = CONFLICT_MESSAGE_IDX + 1usize;
+const INVALID_SECTION_MESSAGE_IDX: usize = SERDE_MESSAGE_IDX + 1usize;
+const AT_LINE_MESSAGE_IDX: usize = INVALID_SECTION_MESSAGE_IDX + 1usize;
+const BAD_QUOTED_VALUE_MESSAGE_IDX: usize = AT_LINE_MESSAGE_IDX + 1usize;
+const LIST_ELEMENT_PARSING_MESSAGE_IDX: usize = BAD_QUOTED_VALUE_MESSAGE_IDX + 1usize;
+const UNDEFINED_VARIABLE_MESSAGE_IDX: usize = LIST_ELEMENT_PARSING_MESSAGE_IDX + 1usize;
+const UNDEFINED_REFERENCE_MESSAGE_IDX: usize = UNDEFINED_VARIABLE_MESSAGE_IDX + 1usize;
+const INTERPOLATION_CYCLE_MESSAGE_IDX: usize = UNDEFINED_REFERENCE_MESSAGE_IDX + 1usize;
+const INCLUDE_CYCLE_MESSAGE_IDX: usize = INTERPOLATION_CYCLE_MESSAGE_IDX + 1usize;
+const INVALID_LAYER_MESSAGE_IDX: usize = INCLUDE_CYCLE_MESSAGE_IDX + 1usize;
+const BAD_OVERRIDE_MESSAGE_IDX: usize = INVALID_LAYER_MESSAGE_IDX + 1usize;
+const VALUE_OUT_OF_RANGE_MESSAGE_IDX: usize = BAD_OVERRIDE_MESSAGE_IDX + 1usize;
+const PATTERN_MISMATCH_MESSAGE_IDX: usize = VALUE_OUT_OF_RANGE_MESSAGE_IDX + 1usize;
+const VALUE_NOT_ALLOWED_MESSAGE_IDX: usize = PATTERN_MISMATCH_MESSAGE_IDX + 1usize;
+const UNKNOWN_SECTION_MESSAGE_IDX: usize = VALUE_NOT_ALLOWED_MESSAGE_IDX + 1usize;
+const UNKNOWN_KEY_MESSAGE_IDX: usize = UNKNOWN_SECTION_MESSAGE_IDX + 1usize;
+const INVALID_SCHEMA_MESSAGE_IDX: usize = UNKNOWN_KEY_MESSAGE_IDX + 1usize;
+const SCHEMA_VIOLATIONS_MESSAGE_IDX: usize = INVALID_SCHEMA_MESSAGE_IDX + 1usize;
 // constant representing the number of errors that rssettings crate can return
-pub const MESSAGES_NUMBER: usize = ALREADY_INITIALIZED_MESSAGE_IDX + 1usize;
+pub const MESSAGES_NUMBER: usize = SCHEMA_VIOLATIONS_MESSAGE_IDX + 1usize;
 
 /// Table of default english language errors
 const SETTINGS_MESSAGES: [&str; MESSAGES_NUMBER] = [
@@ -169,20 +262,391 @@ const SETTINGS_MESSAGES: [&str; MESSAGES_NUMBER] = [
     "Section '{}' key '{}', Parsing error: '{}'",
     "Error writing file: '{}': '{}'",
     "Error reading file: '{}' at line {}: '{}'",
-    "Settings already initialized using file: '{}'"
+    "Settings already initialized using file: '{}'",
+    "Invalid section or key name: '{}'",
+    "Settings file '{}' has been modified since it has been loaded or saved",
+    "Serde error: '{}'",
+    "Section '{}' has missing or invalid keys: {}",
+    "{} at line '{}'",
+    "Bad quoted value at line '{}' of settings file: '{}'",
+    "Section '{}' key '{}' element '{}', Parsing error: '{}'",
+    "Section '{}' key '{}', undefined environment variable '{}'",
+    "Section '{}' key '{}', undefined reference '{}'",
+    "Section '{}' key '{}', interpolation cycle: {}",
+    "Include cycle at line '{}' of settings file '{}': '{}'",
+    "Settings layer '{}' does not exist or is not writable",
+    "Bad override '{}', expected SECTION.key=value",
+    "Section '{}' key '{}', value '{}' out of range '{}'",
+    "Section '{}' key '{}', value '{}' does not match '{}'",
+    "Section '{}' key '{}', value '{}' is not one of '{}'",
+    "Section '{}' not declared in the schema",
+    "Section '{}' key '{}' not declared in the schema",
+    "Invalid schema '{}' at line '{}': '{}'",
+    "Settings '{}' do not match the schema: {}"
 ];
 
 
+/// Errors returned by Settings methods
+/// Every variant mirrors one of the messages of the messages table
+/// and carries the data used to format it, so that callers can match
+/// the kind of error instead of comparing strings.
+/// The 'message' attribute of every variant holds the error formatted
+/// with the messages table of the Settings that generated it (english
+/// or user language, see Settings::new and Settings::new_locale_messages)
+/// and it is what the Display trait renders.
+///
+/// # Examples
+/// ```
+/// use rssettings::{Settings, SettingsError};
+///
+/// fn main() {
+///     let mut settings = Settings::new();
+///     match settings.load("goofy.ini") {
+///         Result::Ok(()) => {
+///         },
+///         Result::Err(SettingsError::OpeningFile { path, .. }) => {
+///             eprintln!("{} does not exist", path);
+///         },
+///         Result::Err(error) => {
+///             eprintln!("{}", error);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub enum SettingsError {
+    /// The settings file could not be opened
+    OpeningFile { path: String, source: io::Error, message: String },
+    /// A section line misses the start section tag '['
+    MissingStartSectionTag { line: usize, path: String, message: String },
+    /// A section line misses the end section tag ']'
+    MissingEndSectionTag { line: usize, path: String, message: String },
+    /// A key/value line misses the assign tag '='
+    MissingAssignTag { line: usize, path: String, message: String },
+    /// A key/value line has an empty key
+    MissingKey { line: usize, path: String, message: String },
+    /// A quoted value misses the closing quote, contains a not valid escape
+    /// sequence or is followed by something else than a comment
+    BadQuotedValue { line: usize, path: String, message: String },
+    /// A key has been defined twice in the same section
+    DuplicatedKey { key: String, line: usize, previous_line: usize, path: String, message: String },
+    /// The requested section does not exist
+    SectionNotFound { section: String, message: String },
+    /// The requested key does not exist in the section
+    KeyNotFound { section: String, key: String, message: String },
+    /// The value of the key can not be parsed in the requested type,
+    /// 'source' holds the parsing error debug representation,
+    /// 'line' is the settings file line of the key, 0 if the key has not been read from the file
+    Parsing { section: String, key: String, line: usize, source: String, message: String },
+    /// An element of a list value (see Settings::get_list) can not be parsed in the requested type,
+    /// 'index' is the position of the element in the list, 'source' holds the parsing error
+    /// debug representation, 'line' is the settings file line of the element
+    ListElementParsing { section: String, key: String, index: usize, line: usize, source: String, message: String },
+    /// The value of the key references an environment variable that is not defined
+    /// and has no default value (see Settings::with_env_interpolation),
+    /// 'line' is the settings file line of the key
+    UndefinedVariable { section: String, key: String, line: usize, variable: String, message: String },
+    /// The value of the key references a key that does not exist
+    /// (see Settings::with_key_interpolation), 'reference' is the text between ${ and },
+    /// 'line' is the settings file line of the key
+    UndefinedReference { section: String, key: String, line: usize, reference: String, message: String },
+    /// The references of the value of the key lead back to an already interpolated key,
+    /// 'chain' holds section, key and line of every key of the cycle,
+    /// from the interpolated key to the one referenced again
+    InterpolationCycle { section: String, key: String, chain: Vec<(String, String, usize)>, message: String },
+    /// An include directive loads a settings file that is already being loaded,
+    /// 'path' is the including file, 'line' the line of the directive
+    /// and 'include' the path of the included file
+    IncludeCycle { line: usize, path: String, include: String, message: String },
+    /// LayeredSettings has no writable layer with the name given to write a value
+    InvalidLayer { layer: String, message: String },
+    /// A string given to Settings::apply_overrides is not a "SECTION.key=value" override
+    BadOverride { argument: String, message: String },
+    /// A numeric value is out of the range declared by the schema (see Settings::validate),
+    /// 'line' is the settings file line of the value, the message shows the range as "min..=max"
+    ValueOutOfRange { section: String, key: String, line: usize, value: String, message: String },
+    /// A value does not match the regular expression declared by the schema,
+    /// 'line' is the settings file line of the value
+    PatternMismatch { section: String, key: String, line: usize, value: String, message: String },
+    /// A value is not one of the values allowed by the schema,
+    /// 'line' is the settings file line of the value
+    ValueNotAllowed { section: String, key: String, line: usize, value: String, message: String },
+    /// A section is not declared by a schema not allowing unknown sections and keys,
+    /// 'line' is the settings file line of the section tag
+    UnknownSection { section: String, line: usize, message: String },
+    /// A key is not declared by a schema not allowing unknown sections and keys,
+    /// 'line' is the settings file line of the key
+    UnknownKey { section: String, key: String, line: usize, message: String },
+    /// A schema file has an invalid attribute at 'line', 'reason' tells why;
+    /// for KeySchema::with_pattern 'path' is the key name and 'line' is 0
+    InvalidSchema { path: String, line: usize, reason: String, message: String },
+    /// Settings do not match a schema (see Settings::validate), 'errors' holds every violation
+    SchemaViolations { path: String, errors: Vec<SettingsError>, message: String },
+    /// The settings file could not be written
+    WritingFile { path: String, source: io::Error, message: String },
+    /// The settings file could not be read
+    ReadingFile { path: String, line: usize, source: io::Error, message: String },
+    /// Settings::load has been called on an already loaded Settings
+    AlreadyInitialized { path: String, message: String },
+    /// A section or key name to insert can not be written in a settings file
+    InvalidName { name: String, message: String },
+    /// Settings::save found the settings file modified by someone else
+    /// and the conflict policy is ConflictPolicy::Fail
+    Conflict { path: String, message: String },
+    /// A struct could not be deserialized from or serialized to Settings
+    /// (see the serde feature), 'source' holds the serde error
+    Serde { source: String, message: String },
+    /// Some keys of a section bound to a struct (see SettingsSection) are missing
+    /// or invalid, 'errors' holds one error for each of them
+    InvalidSection { section: String, errors: Vec<SettingsError>, message: String },
+}
+
+impl SettingsError {
+    /// Returns the error message formatted with the messages table
+    /// of the Settings that generated the error
+    pub fn message(&self) -> &str {
+        match self {
+            SettingsError::OpeningFile { message, .. } |
+            SettingsError::MissingStartSectionTag { message, .. } |
+            SettingsError::MissingEndSectionTag { message, .. } |
+            SettingsError::MissingAssignTag { message, .. } |
+            SettingsError::MissingKey { message, .. } |
+            SettingsError::BadQuotedValue { message, .. } |
+            SettingsError::DuplicatedKey { message, .. } |
+            SettingsError::SectionNotFound { message, .. } |
+            SettingsError::KeyNotFound { message, .. } |
+            SettingsError::Parsing { message, .. } |
+            SettingsError::ListElementParsing { message, .. } |
+            SettingsError::UndefinedVariable { message, .. } |
+            SettingsError::UndefinedReference { message, .. } |
+            SettingsError::InterpolationCycle { message, .. } |
+            SettingsError::IncludeCycle { message, .. } |
+            SettingsError::InvalidLayer { message, .. } |
+            SettingsError::BadOverride { message, .. } |
+            SettingsError::ValueOutOfRange { message, .. } |
+            SettingsError::PatternMismatch { message, .. } |
+            SettingsError::ValueNotAllowed { message, .. } |
+            SettingsError::UnknownSection { message, .. } |
+            SettingsError::UnknownKey { message, .. } |
+            SettingsError::InvalidSchema { message, .. } |
+            SettingsError::SchemaViolations { message, .. } |
+            SettingsError::WritingFile { message, .. } |
+            SettingsError::ReadingFile { message, .. } |
+            SettingsError::AlreadyInitialized { message, .. } |
+            SettingsError::InvalidName { message, .. } |
+            SettingsError::Conflict { message, .. } |
+            SettingsError::Serde { message, .. } |
+            SettingsError::InvalidSection { message, .. } => message
+        }
+    }
+}
+
+// Display trait implementation for SettingsError enumeration
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+// Error trait implementation for SettingsError enumeration
+// source returns the wrapped std::io::Error if any
+impl Error for SettingsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SettingsError::OpeningFile { source, .. } |
+            SettingsError::WritingFile { source, .. } |
+            SettingsError::ReadingFile { source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
+
+
 /// Settings::get method returns this structure.
-/// It is composed by 2 public attributes 
-/// first 'value' is the value returned 
-/// second 'error' can contains the possible error occured 
-/// during the Setting::get method or an empty string in case everything 
+/// It is composed by 2 public attributes
+/// first 'value' is the value returned
+/// second 'error' can contains the possible error occured
+/// during the Setting::get method or None in case everything
 /// has gone well.
 /// see Setting get method for an example
 pub struct SettingsValue<T> {
     pub value: T,
-    pub error: String
+    pub error: Option<SettingsError>
+}
+
+/// Trait of the structs bound to a section of a settings file,
+/// it can be implemented with #[derive(SettingsSection)] (see the derive feature)
+/// # Examples
+/// ```
+/// use rssettings::{Settings, SettingsError, SettingsSection};
+/// 
+/// struct Log {
+///     enabled: bool,
+/// }
+/// 
+/// impl SettingsSection for Log {
+///     fn from_settings(settings: &Settings) -> Result<Self, SettingsError> {
+///         Ok(Self { enabled: settings.get_value("LOG", "enabled")? })
+///     }
+/// 
+///     fn write_to_settings(&self, settings: &mut Settings) -> Result<(), SettingsError> {
+///         settings.set_or_insert("LOG", "enabled", self.enabled)
+///     }
+/// }
+/// 
+/// fn main() {
+///     let mut settings: Settings = "[LOG]\nenabled = true\n".parse().unwrap();
+///     let mut log = Log::from_settings(&settings).unwrap();
+///     log.enabled = false;
+///     log.write_to_settings(&mut settings).unwrap();
+///     assert_eq!(false, settings.get("LOG", "enabled", true).value);
+/// }
+/// ```
+pub trait SettingsSection: Sized {
+    /// Builds the struct from the keys of its section
+    /// Returns std::result::Result::Err(SettingsError) if a key is missing or invalid,
+    /// the derived implementation returns SettingsError::InvalidSection listing all of them
+    ///  * `settings` -  An immutable reference to Setting struct
+    fn from_settings(settings: &Settings) -> StdResult<Self, SettingsError>;
+
+    /// Writes the fields of the struct in the keys of its section,
+    /// missing section and keys are inserted
+    ///  * `self` -  An immutable reference to the struct
+    ///  * `settings` -  A mutable reference to Setting struct
+    fn write_to_settings(&self, settings: &mut Settings) -> StdResult<(), SettingsError>;
+}
+
+/// Enumeration of the policies used by Settings when it is dropped,
+/// see Settings::with_drop_policy
+/// 'Never' Settings is not saved, changes not saved with Settings::save are lost
+/// 'IfDirty' Settings is saved only if it has been changed since it has been
+/// loaded or saved (see Settings::is_dirty), it is the default policy
+/// 'Always' Settings is always saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropPolicy {
+    Never,
+    #[default]
+    IfDirty,
+    Always
+}
+
+/// Enumeration of the policies used by Settings::save when the settings file
+/// has been modified by someone else since it has been loaded or saved,
+/// see Settings::with_conflict_policy
+/// 'Fail' Settings::save returns SettingsError::Conflict, it is the default policy
+/// 'Overwrite' the settings file is overwritten, external changes are lost
+/// 'Merge' the settings file is loaded again and the changes made to Settings
+/// (set, inserted and removed sections/keys) are applied to it before saving,
+/// when the same key has been changed on both sides the Settings value wins;
+/// if a change can not be applied Settings::save returns SettingsError::Conflict
+/// as with 'Fail' and Settings is left unchanged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    #[default]
+    Fail,
+    Overwrite,
+    Merge
+}
+
+/// Enumeration of the multi-line values read by Settings::load,
+/// see Settings::with_multi_line_values
+/// 'Disabled' every line is read on its own, it is the default
+/// 'Backslash' a value ending with a backslash continues on the next line,
+/// the backslash is removed and the next line is appended without its leading spaces
+/// (i.g. "key = first \" followed by "  second" is "first second")
+/// 'Indented' the indented lines following a key/value line are continuation lines
+/// of its value, joined with line feeds without their leading and trailing spaces
+/// (i.g. "key = first" followed by "  second" is "first\nsecond"); an empty line
+/// ends the value, comments are not recognized on continuation lines and
+/// indented keys can not follow a key/value line
+/// 'Both' backslash and indented continuation lines
+/// Quoted values never continue on the next line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultiLineValues {
+    #[default]
+    Disabled,
+    Backslash,
+    Indented,
+    Both
+}
+
+/// Enumeration of the ways section and key names are compared,
+/// see Settings::with_name_matching
+/// 'Exact' names must be equal, it is the default
+/// 'AsciiCaseInsensitive' ASCII letters are compared ignoring case (i.g. "Global" matches "GLOBAL")
+/// 'UnicodeCaseFold' all letters are compared ignoring case (i.g. "ÀREA" matches "àrea"),
+/// using the Unicode lower case mapping of every character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameMatching {
+    #[default]
+    Exact,
+    AsciiCaseInsensitive,
+    UnicodeCaseFold
+}
+
+impl MultiLineValues {
+    // Returns true if values ending with a backslash continue on the next line
+    fn backslash(self) -> bool {
+        matches!(self, MultiLineValues::Backslash | MultiLineValues::Both)
+    }
+
+    // Returns true if indented lines continue the value of the previous line
+    fn indented(self) -> bool {
+        matches!(self, MultiLineValues::Indented | MultiLineValues::Both)
+    }
+}
+
+impl NameMatching {
+    // Returns true if two section or key names match
+    //  * `name` -  A string slice that holds the first name
+    //  * `other` -  A string slice that holds the second name
+    fn matches(self, name: &str, other: &str) -> bool {
+        match self {
+            NameMatching::Exact => name == other,
+            NameMatching::AsciiCaseInsensitive => name.eq_ignore_ascii_case(other),
+            NameMatching::UnicodeCaseFold => name.chars().flat_map(char::to_lowercase).eq(other.chars().flat_map(char::to_lowercase))
+        }
+    }
+}
+
+// Crate private enumeration of the changes made to Settings
+// since it has been loaded or saved, Settings::save applies them
+// again when merging an externally modified settings file
+enum Change {
+    Set(String, String, String),  // section, key, value
+    AddSection(String),  // section
+    RemoveKey(String, String, bool),  // section, key, remove comments
+    RemoveSection(String, bool),  // section, remove comments
+    SetList(String, String, Vec<String>, String)  // section, key, elements, separator
+}
+
+// Crate private structure identifying the content of the settings file
+// as it was loaded or saved: modification time, length and hash of the content
+#[derive(PartialEq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileStamp {
+    // Associated function to create the stamp of a file content
+    //  * `content` -  The file content
+    //  * `modified` -  The file modification time if available
+    fn new(content: &[u8], modified: Option<SystemTime>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(content);
+        Self { modified, len: content.len() as u64, hash: hasher.finish() }
+    }
+}
+
+// Crate private structure holding a settings file loaded
+// by an include directive (see Settings::load)
+struct IncludedFile {
+    file: PathBuf,  // path of the file written by Settings::save
+    path: String,  // path used in error messages
+    document: Document,  // lossless model of the file
+    file_stamp: Option<FileStamp>  // content of the file as it was loaded or saved
 }
 
 // Crate privite enumertion
@@ -190,21 +654,60 @@ pub struct SettingsValue<T> {
 enum LineType {
     EmptyLine,  // Empty line
     SectionLine(String), // Line containing a Section (i.g. [GLOBAL])
-    KeyAndValue(String, String), // Line containing a key value pair, value could be an empty string
-    BadFormattedLine(String) // Bad formatted line the String retirned is the relative error message
+    KeyAndValue(String, KeyValueLine), // Line containing a key value pair and its tokens, value could be an empty string
+    BadFormattedLine(SettingsError) // Bad formatted line the SettingsError returned is the relative error
 }
 
 
 
 /// Setting structure
-/// It is composed by 3 private attributes 
-/// 'path' contains the path of the loaded settings file 
+/// It is composed by the following private attributes 
+/// 'path' contains the path of the loaded settings file or the name of the loaded source
+/// used in error messages
+/// 'file' contains the path of the file written by Settings::save, None if Settings
+/// has been loaded from a reader or a string
+/// 'initialized' tells if Settings has been loaded
 /// 'sections' is a vector containing Section structures inside the settings file
 /// 'messages_table' is a vector of strings representing all error generated by Settings
+/// 'document' is the lossless model of the settings file (comments and spaces included)
+/// used to save it
+/// 'next_id' is the id of the next key/value pair added to a section
+/// 'dirty' tells if Settings has been changed since it has been loaded or saved
+/// 'drop_policy' tells if Settings is saved when dropped
+/// 'drop_error_handler' receives the error of the save done when Settings is dropped
+/// 'conflict_policy' tells Settings::save what to do if the file has been modified by someone else
+/// 'file_stamp' identifies the settings file content as it was loaded or saved
+/// 'changes' are the changes made since Settings has been loaded or saved
+/// 'multi_line_values' tells which lines continue the value of a key/value line
+/// 'array_keys' tells if repeated keys ending with [] are read as the elements of an array
+/// 'env_interpolation' is the source of the environment variables interpolated in values
+/// 'key_interpolation' tells if references to other keys are interpolated in values
+/// 'includes' are the settings files loaded by include directives, in loading order
+/// 'env_overrides' are the environment variables overriding the values read from Settings
+/// 'arg_overrides' are section, key and value of the command line overrides, see Settings::apply_overrides
+/// 'name_matching' tells how section and key names are compared
 pub struct Settings {
     path: String,
+    file: Option<PathBuf>,
+    initialized: bool,
     sections: Vec<Section>,
-    messages_table: Vec<String>
+    messages_table: Vec<String>,
+    document: Document,
+    next_id: usize,
+    dirty: bool,
+    drop_policy: DropPolicy,
+    drop_error_handler: Option<Box<dyn FnMut(SettingsError) + Send>>,
+    conflict_policy: ConflictPolicy,
+    file_stamp: Option<FileStamp>,
+    changes: Vec<Change>,
+    multi_line_values: MultiLineValues,
+    array_keys: bool,
+    env_interpolation: EnvInterpolation,
+    key_interpolation: bool,
+    includes: Vec<IncludedFile>,
+    env_overrides: EnvOverrides,
+    arg_overrides: Vec<(String, String, String)>,
+    name_matching: NameMatching
 }
 
 
@@ -224,15 +727,7 @@ impl Settings {
     /// ```
     /// 
     pub fn new() -> Self {
-        let mut settings = Self {
-            path: String::from(""),
-            sections: vec![],
-            messages_table: vec![]
-        };
-        for message in SETTINGS_MESSAGES {
-            settings.messages_table.push(message.to_string());
-        }
-        settings
+        Self::new_locale_messages(&SETTINGS_MESSAGES)
     }
 
 
@@ -255,7 +750,27 @@ impl Settings {
     ///     "Sezione '{}' chiave '{}', Errore di analisi: '{}'",
     ///     "Errore scrittura file: '{}': '{}'",
     ///     "Errore lettura file: '{}' alla line {}: '{}'",
-    ///     "Settings già inizializzato utilizzando il file: '{}'"
+    ///     "Settings già inizializzato utilizzando il file: '{}'",
+    ///     "Nome di sezione o chiave non valido: '{}'",
+    ///     "Il file di settings '{}' è stato modificato dopo il caricamento o il salvataggio",
+    ///     "Errore serde: '{}'",
+    ///     "La sezione '{}' ha chiavi mancanti o non valide: {}",
+    ///     "{} alla linea '{}'",
+    ///     "Valore tra virgolette non valido alla linea '{}' del file di settings: '{}'",
+    ///     "Sezione '{}' chiave '{}' elemento '{}', Errore di analisi: '{}'",
+    ///     "Sezione '{}' chiave '{}', variabile d'ambiente '{}' non definita",
+    ///     "Sezione '{}' chiave '{}', riferimento '{}' non definito",
+    ///     "Sezione '{}' chiave '{}', ciclo di interpolazione: {}",
+    ///     "Inclusione ciclica alla linea '{}' del file di settings '{}': '{}'",
+    ///     "Il livello di settings '{}' non esiste o non è scrivibile",
+    ///     "Override '{}' errato, atteso SEZIONE.chiave=valore",
+    ///     "Sezione '{}' chiave '{}', valore '{}' fuori dall'intervallo '{}'",
+    ///     "Sezione '{}' chiave '{}', valore '{}' non corrisponde a '{}'",
+    ///     "Sezione '{}' chiave '{}', valore '{}' non è tra '{}'",
+    ///     "Sezione '{}' non dichiarata nello schema",
+    ///     "Sezione '{}' chiave '{}' non dichiarata nello schema",
+    ///     "Schema '{}' non valido alla linea '{}': '{}'",
+    ///     "Settings '{}' non conformi allo schema: {}"
     /// ];
     /// fn main()  {
     ///     let mut settings = Settings::new_locale_messages(&IT_SETTINGS_MESSAGES);
@@ -274,8 +789,26 @@ impl Settings {
     pub fn new_locale_messages(settings_messages: &[&str; MESSAGES_NUMBER]) -> Self {
         let mut settings = Self {
             path: String::from(""),
+            file: None,
+            initialized: false,
             sections: vec![],
-            messages_table: vec![]
+            messages_table: vec![],
+            document: Document::default(),
+            next_id: 1,
+            dirty: false,
+            drop_policy: DropPolicy::default(),
+            drop_error_handler: None,
+            conflict_policy: ConflictPolicy::default(),
+            file_stamp: None,
+            changes: vec![],
+            multi_line_values: MultiLineValues::default(),
+            array_keys: false,
+            env_interpolation: EnvInterpolation::default(),
+            key_interpolation: false,
+            includes: vec![],
+            env_overrides: EnvOverrides::default(),
+            arg_overrides: vec![],
+            name_matching: NameMatching::default()
         };
         for message in *settings_messages {
             settings.messages_table.push(message.to_string());
@@ -284,326 +817,1295 @@ impl Settings {
 
     }
 
-    /// Load a settings file 
-    /// Returns std::result::Result::Ok(()) if settings file correctly loaded
-    /// or std::result::Result::Err(error: String) if something has gone wrong
-    /// error contains a message (in english or user language according to 
-    /// user created Setting, Settings::new or Settings::new_locale_messages) 
-    /// describing the problem
-    /// 
+    /// Sets the policy used to save Settings when it is dropped,
+    /// the default policy is DropPolicy::IfDirty
+    /// Returns the Setting structure with the new policy
     /// # Examples
     /// ```
-    /// use rssettings::Settings;
+    /// use rssettings::{Settings, DropPolicy};
     /// 
     /// fn main() {
-    ///     let mut settings = Settings::new();
-    ///     match settings.load("test_files/settings.ini") {
-    ///         Result::Ok(()) => {
-    ///         },
-    ///         Result::Err(error) => {
-    ///             eprintln!("{}", error);
-    ///         }
+    ///     let mut settings = Settings::new().with_drop_policy(DropPolicy::Never);
+    ///     if let Result::Ok(()) = settings.load("test_files/settings.ini") {
+    ///         // not written in the settings file
+    ///         let _ = settings.set("GLOBAL", "bool_value", false);
     ///     }
     /// }
     /// ```
     /// 
-    ///  * `self` -  An mutable reference to Setting struct
-    ///  * `path` -  A Path as reference to the settings file to load
+    ///  * `self` -  The Setting struct
+    ///  * `drop_policy` -  The policy used when Settings is dropped
     /// 
-    pub fn load<P>(&mut self, path: P) -> StdResult<(), String> where P: AsRef<Path> {
-        if self.is_initialize() {
-            return StdResult::Err(
-                self.format_message(ALREADY_INITIALIZED_MESSAGE_IDX, vec![&self.path]));
-        }
-
-        let result = self.load_private(path);
-        if StdResult::Ok(()) != result {
-            self.unload();
-        }
-        result
+    pub fn with_drop_policy(mut self, drop_policy: DropPolicy) -> Self {
+        self.drop_policy = drop_policy;
+        self
     }
 
-
-    /// Save Settings in the file used to load it
-    /// User can save Settings every time it changes one of its section/key_value pair
-    /// or let the Settings save itself when it is dropped
-    /// see trait Drop implementation
-    /// Returns std::result::Result::Ok(()) when saving is successfuly done
-    /// or std::result::Result::Err(error: String) when a problem occured
-    /// error message language depends on new associated function used to crete Settings 
+    /// Sets the handler receiving the error of the save done when Settings is dropped,
+    /// without a handler the error message is printed as a line on the standard error
+    /// Returns the Setting structure with the new handler
     /// # Examples
     /// ```
-    /// use rssettings::Settings;
+    /// use rssettings::{Settings, SettingsError};
+    /// use std::sync::{Arc, Mutex};
     /// 
     /// fn main() {
-    ///     let mut settings = Settings::new();
-    ///     match settings.load("test_files/settings.ini") {
-    ///         Result::Ok(()) => {
-    ///             if let Result::Ok(()) = settings.set("GLOBAL", "bool_value", true) {
-    ///                 if let Result::Err(error) = settings.save() {
-    ///                     eprintln!("{}", error);
-    ///                 }
-    ///             }
-    ///         },
-    ///         Result::Err(error) => {
-    ///             eprintln!("{}", error);
-    ///         }
-    ///     }
+    ///     let drop_error: Arc<Mutex<Option<SettingsError>>> = Arc::new(Mutex::new(None));
+    ///     let handler_error = Arc::clone(&drop_error);
+    ///     let settings = Settings::new().with_drop_error_handler(move |error| {
+    ///         *handler_error.lock().unwrap() = Some(error);
+    ///     });
+    ///     drop(settings);
+    ///     assert!(drop_error.lock().unwrap().is_none());
     /// }
     /// ```
-    ///  * `self` -  A mutable reference to Setting struct
     /// 
-    pub fn save(&self) -> StdResult<(), String> {
-        let mut line_texts: Vec<String> = vec![];
-        if self.is_initialize() {
-            match File::open(&self.path) {
-                IoResult::Ok(settings_file) => {
-                    let lines = io::BufReader::new(settings_file).lines();
-                    let mut line_cnt = 1usize;
-                    for line in lines {
-                        match line {
-                            IoResult::Ok(line_text) => {
-                                line_texts.push(line_text);
-                            },
-                            IoResult::Err(ioerror) => {
-                                let error = format!("{:#}", ioerror);
-                                let line = format!("{}", line_cnt);
-                                return StdResult::Err(self.format_message(READING_FILE_ERROR_MESSAGE_IDX, 
-                                    vec![&self.path, &line, &error]));            
-                                }           
-                        }
-                        line_cnt = line_cnt + 1;
-                    }
-                },
-                IoResult::Err(ioerror) => {
-                    let error = format!("{:#}", ioerror);
-                    return StdResult::Err(self.format_message(OPENING_FILE_ERROR_MESSAGE_IDX,
-                        vec![&self.path, &error]));
-                }
-            }
-            let mut sections_iter = self.sections.iter();
-            while let Some(section) = sections_iter.next() {
-                let mut values_iter = section.values.iter();
-                while let Some(key_value) = values_iter.next() {
-                    if let Some(index) = line_texts[key_value.line_cnt - 1].find(COMMENT_TAG) {
-                        let comment = &line_texts[key_value.line_cnt - 1][index..];
-                        line_texts[key_value.line_cnt - 1] = format!("{} {} {} {}", key_value.key, ASSIGN_TAG, key_value.value, comment);
-                    } else {
-                        line_texts[key_value.line_cnt - 1] = format!("{} {} {}", key_value.key, ASSIGN_TAG, key_value.value);
-                    }
-                }
-            }
-    
-            match File::create(&self.path) {
-                IoResult::Ok(mut settings_file) => {
-                    for line_text in line_texts {
-                        if let IoResult::Err(ioerror) = settings_file.write_all(format!("{}\n", line_text).as_bytes()) {
-                            let error = format!("{:#}", ioerror);
-                            return StdResult::Err(self.format_message(WRITING_FILE_ERROR_MESSAGE_IDX,
-                                vec![&self.path, &error]));
-                        } else {
-                            if let IoResult::Err(ioerror) = settings_file.flush() {
-                                let error = format!("{:#}", ioerror);
-                                return StdResult::Err(self.format_message(WRITING_FILE_ERROR_MESSAGE_IDX,
-                                    vec![&self.path, &error]));
-                            }
-                        }
-                    }
-                },
-                IoResult::Err(ioerror) => {
-                    let error = format!("{:#}", ioerror);
-                    return StdResult::Err(self.format_message(OPENING_FILE_ERROR_MESSAGE_IDX,
-                        vec![&self.path, &error]));
-                }
-            }
-        }
-        StdResult::Ok(())
+    ///  * `self` -  The Setting struct
+    ///  * `handler` -  The function called with the drop time save error
+    /// 
+    pub fn with_drop_error_handler<F>(mut self, handler: F) -> Self where F: FnMut(SettingsError) + Send + 'static {
+        self.drop_error_handler = Some(Box::new(handler));
+        self
     }
 
-
-    /// Generic method use to get section/key value
-    /// Generic type parameter has to implement FromStr & Display traits
-    /// Returns a SettingsValue structure containing the value associated with the section
-    /// and the key if both exist or a default value if not ad SettingsValue.error set with the 
-    /// relative error message  
-    /// error message language depends on new associated function used to crete Settings 
+    /// Sets the policy used by Settings::save when the settings file has been
+    /// modified by someone else since it has been loaded or saved,
+    /// the default policy is ConflictPolicy::Fail
+    /// Returns the Setting structure with the new policy
     /// # Examples
     /// ```
-    /// use rssettings::Settings;
+    /// use rssettings::{Settings, ConflictPolicy};
     /// 
     /// fn main() {
-    ///     let mut settings = Settings::new();
-    ///     match settings.load("test_files/settings.ini") {
-    ///         Result::Ok(()) => {
-    ///             let bool_value = settings.get("GLOBAL", "bool_value", false);
-    ///             if bool_value.error.len() == 0 {
-    ///                 assert_eq!(true, bool_value.value);
-    ///             } else {
-    ///                 eprintln!("{}", bool_value.error);
-    ///             }
-    ///         },
-    ///         Result::Err(error) => {
-    ///             eprintln!("{}", error);
-    ///         }
+    ///     let mut settings = Settings::new().with_conflict_policy(ConflictPolicy::Merge);
+    ///     if let Result::Ok(()) = settings.load("test_files/settings.ini") {
+    ///         let _ = settings.set("GLOBAL", "bool_value", true);
+    ///         // external changes made to the file in the meantime are kept
+    ///         let _ = settings.save();
     ///     }
     /// }
     /// ```
     /// 
-    ///  * `self` -  An immutable reference to Setting struct
-    ///  * `section_name` -  A string slice that holds the name of the Section
-    ///  * `key` -  A string slice that holds the name of the key inside the Section
-    ///  * `T` -  A default generic value returned in case an error occurs
-    ///  
-    pub fn get<T: FromStr + Display>(&self, section_name: &str, key: &str, default_value: T) -> SettingsValue<T> where <T as FromStr>::Err: Debug {
-        let mut result = SettingsValue {value: default_value, error: String::from("")};
-
-        if let Some(section) = self.get_section(section_name) {
-            if let Some(value) = section.get(key) {
-                match value.parse::<T>() {
-                    StdResult::Ok(parsed_value) => {
-                        result.value = parsed_value;
-                    },
-                    StdResult::Err(error) => {
-                        let error = format!("{:#?}", error);
-                        let sname = section_name.to_string();
-                        let kname = key.to_string();
-                        result.error = self.format_message(PARSING_ERROR_MESSAGE_IDX, 
-                            vec![&sname, &kname, &error]);
-                    }
-                }
-            } else {
-                let sname = section_name.to_string();
-                let kname = key.to_string();
-                result.error = self.format_message(KEY_NOT_FOUND_MESSAGE_IDX, 
-                    vec![&sname, &kname]);
-            }
-        } else {
-            let sname = section_name.to_string();
-            result.error = self.format_message(SECTION_NOT_FOUND_MESSAGE_IDX, 
-                vec![&sname]);
-        }
-
-        result
+    ///  * `self` -  The Setting struct
+    ///  * `conflict_policy` -  The policy used when the settings file has been modified
+    /// 
+    pub fn with_conflict_policy(mut self, conflict_policy: ConflictPolicy) -> Self {
+        self.conflict_policy = conflict_policy;
+        self
     }
 
-
+    /// Sets which lines continue the value of a key/value line when
+    /// the settings file is loaded, multi-line values are disabled by default
+    /// Changed multi-line values are written back on indented continuation lines
+    /// with MultiLineValues::Indented and MultiLineValues::Both, values continued
+    /// with a backslash are written back on a single line
+    /// Returns the Setting structure with the new setting
     /// # Examples
     /// ```
-    /// use rssettings::Settings;
+    /// use rssettings::{Settings, MultiLineValues};
     /// 
     /// fn main() {
-    ///     let mut settings = Settings::new();
-    ///     match settings.load("test_files/settings.ini") {
-    ///         Result::Ok(()) => {
-    ///             let orig_bool_value = settings.get("GLOBAL", "bool_value", false);
-    ///             if orig_bool_value.error.len() == 0 {
-    ///                 settings.set("GLOBAL", "bool_value", false).unwrap_or_else(|error| {
-    ///                     eprintln!("{}", error);
-    ///                 });
-    ///                 let new_bool_value = settings.get("GLOBAL", "bool_value", true);
-    ///                 assert_ne!(orig_bool_value.value, new_bool_value.value);
-    ///                 settings.set("GLOBAL", "bool_value", true).unwrap_or_else(|error| {
-    ///                     eprintln!("{}", error);
-    ///                 });
-    ///             } else {
-    ///                 eprintln!("{}", orig_bool_value.error);
-    ///             }
-    ///         },
-    ///         Result::Err(error) => {
-    ///             eprintln!("{}", error);
-    ///         }
-    ///     }
+    ///     let text = "[SQL]\nquery = SELECT *\n    FROM users\n";
+    ///     let mut settings = Settings::new().with_multi_line_values(MultiLineValues::Indented);
+    ///     settings.load_from_reader(text.as_bytes(), "sql.ini").unwrap();
+    ///     assert_eq!("SELECT *\nFROM users", settings.get("SQL", "query", String::new()).value);
     /// }
     /// ```
     /// 
-    ///  * `self` -  A mutable reference to Setting struct
-    ///  * `section_name` -  A string slice that holds the name of the Section
-    ///  * `key` -  A string slice that holds the name of the key inside the Section
-    ///  * `T` -  A new value for the key 
+    ///  * `self` -  The Setting struct
+    ///  * `multi_line_values` -  The lines continuing a value
     /// 
-    pub fn set<T: Display>(&mut self, section_name: &str, key: &str, value: T) -> StdResult<(), String> {
-        if let Some(section) = self.get_section_mut(section_name) {
-            if !section.set(key, value.to_string()) {
-                let sname = section_name.to_string();
-                let kname = key.to_string();
-                return StdResult::Err(self.format_message(KEY_NOT_FOUND_MESSAGE_IDX, 
-                    vec![&sname, &kname]));
-            }
-            StdResult::Ok(())
-        } else {
-            let sname = section_name.to_string();
-            return StdResult::Err(self.format_message(SECTION_NOT_FOUND_MESSAGE_IDX, 
-                vec![&sname]));
-        }
+    pub fn with_multi_line_values(mut self, multi_line_values: MultiLineValues) -> Self {
+        self.multi_line_values = multi_line_values;
+        self
     }
 
-    /// Returns if a section exists
+    /// Returns which lines continue the value of a key/value line
+    ///  * `self` -  An immutable reference to Setting struct
     /// 
+    pub fn multi_line_values(&self) -> MultiLineValues {
+        self.multi_line_values
+    }
+
+    /// Sets if repeated keys ending with [] are read as the elements of an array
+    /// (i.g. "host[] = a" and "host[] = b"), they are not reported as duplicated keys
+    /// and Settings::get_list returns all of them, arrays are disabled by default
+    /// Returns the Setting structure with the new setting
     /// # Examples
     /// ```
     /// use rssettings::Settings;
     /// 
     /// fn main() {
-    ///     let mut settings = Settings::new();
-    ///     match settings.load("test_files/settings.ini") {
-    ///         Result::Ok(()) => {
-    ///             if settings.section_exists("GOOFY") {
-    ///                 println!("[GOOFY] exists"); 
-    ///             } else {
-    ///                 eprintln!("[GOOFY] does not exist");
-    ///             }
-    ///         },
-    ///         Result::Err(error) => {
-    ///             eprintln!("{}", error);
-    ///         }
-    ///     }
+    ///     let text = "[SERVERS]\nhost[] = alpha\nhost[] = beta\n";
+    ///     let mut settings = Settings::new().with_array_keys(true);
+    ///     settings.load_from_reader(text.as_bytes(), "servers.ini").unwrap();
+    ///     assert_eq!(vec!["alpha", "beta"], settings.get_list::<String>("SERVERS", "host", ",").unwrap());
     /// }
     /// ```
+    /// 
+    ///  * `self` -  The Setting struct
+    ///  * `array_keys` -  If true repeated keys ending with [] are array elements
+    /// 
+    pub fn with_array_keys(mut self, array_keys: bool) -> Self {
+        self.array_keys = array_keys;
+        self
+    }
+
+    /// Returns if repeated keys ending with [] are read as the elements of an array
     ///  * `self` -  An immutable reference to Setting struct
-    ///  * `section_name` -  A string slice that holds the name of the Section
     /// 
-    pub fn section_exists(&self, section_name: &str) -> bool {
-        let mut result = false;
-        if let Some(_) = self.get_section(section_name) {
-            result = true;
-        }
-        result
+    pub fn array_keys(&self) -> bool {
+        self.array_keys
     }
 
-    /// Returns if a key exists in a specific section
+    /// Sets the source of the environment variables interpolated in the values
+    /// returned by Settings::get, Settings::get_value and Settings::get_list:
+    /// ${NAME} is replaced with the value of the variable NAME, ${NAME:-default} with
+    /// 'default' if NAME is not defined or empty, $$ is a single $
+    /// Values are saved with their references, Settings::get_raw returns them
+    /// as they are written in the settings file; interpolation is disabled by default
+    /// Returns the Setting structure with the new setting
     /// # Examples
     /// ```
-    /// use rssettings::Settings;
+    /// use rssettings::{Settings, EnvInterpolation};
+    /// use std::collections::HashMap;
     /// 
     /// fn main() {
-    ///     let mut settings = Settings::new();
-    ///     match settings.load("test_files/settings.ini") {
-    ///         Result::Ok(()) => {
-    ///             if settings.key_exists("GLOBAL", "enabled") {
-    ///                 println!("key 'enabled' in section 'GLOBAL' exists"); 
-    ///             } else {
-    ///                 eprintln!("key 'enabled' in section 'GLOBAL' does not exist"); 
-    ///             }
-    ///         },
-    ///         Result::Err(error) => {
-    ///             eprintln!("{}", error);
-    ///         }
-    ///     }
+    ///     let variables = HashMap::from([("HOME".to_string(), "/home/user".to_string())]);
+    ///     let mut settings = Settings::new().with_env_interpolation(EnvInterpolation::Map(variables));
+    ///     settings.load_from_reader("[LOG]\ndir = ${HOME}/logs\nlevel = ${LEVEL:-3}\n".as_bytes(), "log.ini").unwrap();
+    ///     assert_eq!("/home/user/logs", settings.get("LOG", "dir", String::new()).value);
+    ///     assert_eq!(3, settings.get("LOG", "level", 0).value);
+    ///     assert_eq!("${HOME}/logs", settings.get_raw("LOG", "dir").unwrap());
     /// }
     /// ```
     /// 
+    ///  * `self` -  The Setting struct
+    ///  * `env_interpolation` -  The source of the environment variables
+    /// 
+    pub fn with_env_interpolation(mut self, env_interpolation: EnvInterpolation) -> Self {
+        self.env_interpolation = env_interpolation;
+        self
+    }
+
+    /// Returns the source of the environment variables interpolated in values
     ///  * `self` -  An immutable reference to Setting struct
-    ///  * `section_name` -  A string slice that holds the name of the Section
-    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    /// 
+    pub fn env_interpolation(&self) -> &EnvInterpolation {
+        &self.env_interpolation
+    }
+
+    /// Sets if references to other keys are interpolated in the values returned by
+    /// Settings::get, Settings::get_value and Settings::get_list: ${key} is replaced with the
+    /// value of a key of the same section, ${SECTION:key} with the value of a key of another section
+    /// References are resolved when values are read, the referenced values are interpolated too
+    /// and a reference cycle is reported with SettingsError::InterpolationCycle; ${key} is an
+    /// environment variable if the key does not exist and environment interpolation is enabled
+    /// (see Settings::with_env_interpolation); key interpolation is disabled by default
+    /// Returns the Setting structure with the new setting
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let text = "[PATHS]\nbase = /opt/app\nlogs = ${base}/logs\n[LOG]\nfile = ${PATHS:logs}/app.log\n";
+    ///     let mut settings = Settings::new().with_key_interpolation(true);
+    ///     settings.load_from_reader(text.as_bytes(), "app.ini").unwrap();
+    ///     assert_eq!("/opt/app/logs/app.log", settings.get("LOG", "file", String::new()).value);
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  The Setting struct
+    ///  * `key_interpolation` -  If true references to other keys are interpolated
+    /// 
+    pub fn with_key_interpolation(mut self, key_interpolation: bool) -> Self {
+        self.key_interpolation = key_interpolation;
+        self
+    }
+
+    /// Returns if references to other keys are interpolated in values
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn key_interpolation(&self) -> bool {
+        self.key_interpolation
+    }
+
+    /// Sets the environment variables overriding the values returned by Settings::get,
+    /// Settings::get_value, Settings::get_list and Settings::deserialize
+    /// (i.g. APP__DATABASE__PORT=5433 overrides the key 'port' of the section 'DATABASE'
+    /// with prefix "APP" and separator "__", see EnvOverrides)
+    /// An overriding value is returned in place of the file value, even if the key does not
+    /// exist in the settings file, and it is interpolated as the file values are; overriding
+    /// values are never written by Settings::save, the values set in Settings are saved
+    /// Returns the Setting structure with the new setting
+    /// # Examples
+    /// ```
+    /// use rssettings::{EnvOverrides, Settings};
+    /// 
+    /// fn main() {
+    ///     let variables = vec![("APP__DATABASE__PORT".to_string(), "5433".to_string())];
+    ///     let mut settings = Settings::new().with_env_overrides(EnvOverrides::from_variables("APP", "__", variables));
+    ///     settings.load_from_reader("[DATABASE]\nport = 5432\n".as_bytes(), "app.ini").unwrap();
+    ///     assert_eq!(5433, settings.get("DATABASE", "port", 0).value);
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  The Setting struct
+    ///  * `env_overrides` -  The overriding variables, EnvOverrides::new reads the process environment
+    /// 
+    pub fn with_env_overrides(mut self, env_overrides: EnvOverrides) -> Self {
+        self.env_overrides = env_overrides;
+        self
+    }
+
+    /// Returns the environment variables overriding the values read from Settings
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn env_overrides(&self) -> &EnvOverrides {
+        &self.env_overrides
+    }
+
+    /// Sets how section and key names are compared when they are looked up, when
+    /// duplicated keys are detected and when repeated section tags are merged while
+    /// loading; names are saved as they have been written, the default is NameMatching::Exact
+    /// It should be set before loading: keys already loaded are not merged
+    /// Returns the Setting structure with the new setting
+    /// # Examples
+    /// ```
+    /// use rssettings::{NameMatching, Settings};
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new().with_name_matching(NameMatching::AsciiCaseInsensitive);
+    ///     settings.load_from_reader("[Log]\nEnabled = true\n".as_bytes(), "app.ini").unwrap();
+    ///     assert_eq!(true, settings.get("LOG", "enabled", false).value);
+    ///     settings.set("log", "ENABLED", false).unwrap();
+    ///     let mut buffer: Vec<u8> = vec![];
+    ///     settings.write_to(&mut buffer).unwrap();
+    ///     assert_eq!("[Log]\nEnabled = false\n", String::from_utf8(buffer).unwrap());
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  The Setting struct
+    ///  * `name_matching` -  How section and key names are compared
+    /// 
+    pub fn with_name_matching(mut self, name_matching: NameMatching) -> Self {
+        self.name_matching = name_matching;
+        for section in &mut self.sections {
+            section.name_matching = name_matching;
+        }
+        self
+    }
+
+    /// Returns how section and key names are compared
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn name_matching(&self) -> NameMatching {
+        self.name_matching
+    }
+
+    /// Returns the policy used by Settings::save when the settings file
+    /// has been modified by someone else
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn conflict_policy(&self) -> ConflictPolicy {
+        self.conflict_policy
+    }
+
+    /// Returns the policy used to save Settings when it is dropped
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn drop_policy(&self) -> DropPolicy {
+        self.drop_policy
+    }
+
+    /// Returns if Settings has been changed (set, inserted or removed sections/keys)
+    /// since it has been loaded or last saved
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     assert!(!settings.is_dirty());
+    ///     settings.set_or_insert("LOG", "log_level", 3).unwrap();
+    ///     assert!(settings.is_dirty());
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Load a settings file 
+    /// Returns std::result::Result::Ok(()) if settings file correctly loaded
+    /// or std::result::Result::Err(error: SettingsError) if something has gone wrong
+    /// error displays a message (in english or user language according to 
+    /// user created Setting, Settings::new or Settings::new_locale_messages) 
+    /// describing the problem
+    /// A line "!include path" loads another settings file and a line "!includedir path"
+    /// loads the files of a directory sorted by name, hidden files excluded; relative
+    /// paths start from the directory of the including file. Included files begin in the
+    /// GLOBAL section and their key/value pairs are merged in the sections in loading order,
+    /// a key read again from another file overrides the previous value.
+    /// Settings::save writes every key/value pair in the file it has been read from
+    /// (see Settings::key_file), a file including itself directly or through other files
+    /// is reported with SettingsError::IncludeCycle
+    /// 
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     match settings.load("test_files/settings.ini") {
+    ///         Result::Ok(()) => {
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An mutable reference to Setting struct
+    ///  * `path` -  A Path as reference to the settings file to load
+    /// 
+    pub fn load<P>(&mut self, path: P) -> StdResult<(), SettingsError> where P: AsRef<Path> {
+        if self.is_initialize() {
+            let message = self.format_message(ALREADY_INITIALIZED_MESSAGE_IDX, vec![&self.path]);
+            return StdResult::Err(SettingsError::AlreadyInitialized { path: self.path.clone(), message });
+        }
+
+        let result = self.load_private(path);
+        if result.is_err() {
+            self.unload();
+        }
+        result
+    }
+
+    /// Load settings from a reader (i.g. std::io::stdin().lock() or a byte slice)
+    /// 'name' is used in place of the settings file path in error messages,
+    /// Settings loaded from a reader are not written by Settings::save,
+    /// use Settings::write_to or Settings::save_as instead
+    /// Returns std::result::Result::Ok(()) if settings correctly loaded
+    /// or std::result::Result::Err(error: SettingsError) if something has gone wrong
+    /// 
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     let text = "[LOG]\nenabled = true\n";
+    ///     match settings.load_from_reader(text.as_bytes(), "embedded settings") {
+    ///         Result::Ok(()) => {
+    ///             assert_eq!(true, settings.get("LOG", "enabled", false).value);
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An mutable reference to Setting struct
+    ///  * `reader` -  The reader the settings are read from
+    ///  * `name` -  A string slice that holds the name of the settings source
+    /// 
+    pub fn load_from_reader<R: BufRead>(&mut self, reader: R, name: &str) -> StdResult<(), SettingsError> {
+        if self.is_initialize() {
+            let message = self.format_message(ALREADY_INITIALIZED_MESSAGE_IDX, vec![&self.path]);
+            return StdResult::Err(SettingsError::AlreadyInitialized { path: self.path.clone(), message });
+        }
+
+        let result = self.read_document(reader, name, 0, None, &mut vec![]);
+        match result {
+            StdResult::Ok(()) => {
+                self.path = name.to_string();
+                self.initialized = true;
+            },
+            StdResult::Err(_) => {
+                self.unload();
+            }
+        }
+        result
+    }
+
+
+    /// Loads again the settings file, changes not yet saved are discarded
+    /// If the file can not be loaded Settings keeps its current content
+    /// and the error is returned, Settings loaded from a reader or a string
+    /// are left unchanged
+    /// Returns std::result::Result::Ok(()) if settings file correctly loaded
+    /// or std::result::Result::Err(error: SettingsError) if something has gone wrong
+    /// 
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     if let Result::Ok(()) = settings.load("test_files/settings.ini") {
+    ///         let _ = settings.set("GLOBAL", "bool_value", false);
+    ///         if let Result::Err(error) = settings.reload() {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///         assert_eq!(true, settings.get("GLOBAL", "bool_value", false).value);
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An mutable reference to Setting struct
+    /// 
+    pub fn reload(&mut self) -> StdResult<(), SettingsError> {
+        if let Some(file) = self.file.clone() {
+            let mut loaded = self.load_again(&file)?;
+            self.take_content(&mut loaded);
+            self.changes.clear();
+            self.dirty = false;
+        }
+        StdResult::Ok(())
+    }
+
+    /// Save Settings in the file used to load it
+    /// User can save Settings every time it changes one of its section/key_value pair
+    /// or let the Settings save itself when it is dropped
+    /// see trait Drop implementation and Settings::with_drop_policy
+    /// The file is written from the lines kept in memory by Settings::load:
+    /// only the text of modified values changes, comments, empty lines, spaces and
+    /// line endings are written as they were read, so saving an unchanged Settings
+    /// writes back exactly the loaded file.
+    /// Key/value pairs inserted with Settings::set_or_insert are written after the
+    /// last line of their section, inserted sections are appended at the end of the file,
+    /// lines of key/value pairs and sections removed with Settings::remove_key and
+    /// Settings::remove_section are deleted
+    /// The file is never left truncated: settings are written to a temporary file
+    /// in the same directory, which takes the permissions of the settings file,
+    /// and then the temporary file replaces the settings file
+    /// If the settings file has been modified by someone else since it has been loaded
+    /// or saved, Settings::save fails, overwrites it or merges the changes made to Settings
+    /// according to the conflict policy (see Settings::with_conflict_policy)
+    /// Returns std::result::Result::Ok(()) when saving is successfuly done
+    /// or std::result::Result::Err(error: SettingsError) when a problem occured
+    /// error message language depends on new associated function used to crete Settings 
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     match settings.load("test_files/settings.ini") {
+    ///         Result::Ok(()) => {
+    ///             if let Result::Ok(()) = settings.set("GLOBAL", "bool_value", true) {
+    ///                 if let Result::Err(error) = settings.save() {
+    ///                     eprintln!("{}", error);
+    ///                 }
+    ///             }
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///  * `self` -  A mutable reference to Setting struct
+    /// 
+    pub fn save(&mut self) -> StdResult<(), SettingsError> {
+        if let Some(file) = self.file.clone() {
+            if let Some(path) = self.modified_file_path(&file) {
+                match self.conflict_policy {
+                    ConflictPolicy::Fail => {
+                        let message = self.format_message(CONFLICT_MESSAGE_IDX, vec![&path]);
+                        return StdResult::Err(SettingsError::Conflict { path, message });
+                    },
+                    ConflictPolicy::Overwrite => {
+                    },
+                    ConflictPolicy::Merge => {
+                        self.merge(&file, path)?;
+                    }
+                }
+            }
+            let path = self.path.clone();
+            self.save_private(&file, &path)?;
+        }
+        StdResult::Ok(())
+    }
+
+    /// Save Settings in a new file that becomes the file
+    /// written by the following calls to Settings::save
+    /// It can be used to create a new settings file or to save Settings loaded
+    /// from a reader, see Settings::save for a description of how the file is written
+    /// Settings loaded from a file with include directives can not be saved to a new file:
+    /// the included files would be written to their old paths while the copied
+    /// "!include" lines would be resolved from the new file
+    /// Returns std::result::Result::Ok(()) when saving is successfuly done
+    /// or std::result::Result::Err(error: SettingsError) when a problem occured,
+    /// SettingsError::WritingFile if Settings has included files
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     settings.set_or_insert("LOG", "enabled", true).unwrap();
+    ///     let path = std::env::temp_dir().join("rssettings_save_as_example.ini");
+    ///     if let Result::Err(error) = settings.save_as(&path) {
+    ///         eprintln!("{}", error);
+    ///     }
+    ///     # let _ = std::fs::remove_file(&path);
+    /// }
+    /// ```
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `path` -  A Path as reference to the settings file to write
+    /// 
+    pub fn save_as<P>(&mut self, path: P) -> StdResult<(), SettingsError> where P: AsRef<Path> {
+        let path_str = path.as_ref().as_os_str().to_str().unwrap_or("").to_string();
+        if !self.includes.is_empty() {
+            let ioerror = io::Error::new(io::ErrorKind::Unsupported, "settings with included files can not be saved to a new file");
+            return StdResult::Err(self.writing_file_error(&path_str, ioerror));
+        }
+        self.save_private(path.as_ref(), &path_str)?;
+        self.path = path_str;
+        self.file = Some(path.as_ref().to_path_buf());
+        self.initialized = true;
+        StdResult::Ok(())
+    }
+
+    /// Write Settings to a writer (i.g. std::io::stdout() or a Vec<u8>)
+    /// see Settings::save for a description of how settings are written
+    /// Returns std::result::Result::Ok(()) when writing is successfuly done
+    /// or std::result::Result::Err(error: SettingsError) when a problem occured
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "[LOG]\nenabled = true # comment\n".parse().unwrap();
+    ///     let mut buffer: Vec<u8> = vec![];
+    ///     if let Result::Err(error) = settings.write_to(&mut buffer) {
+    ///         eprintln!("{}", error);
+    ///     }
+    ///     assert_eq!("[LOG]\nenabled = true # comment\n", String::from_utf8(buffer).unwrap());
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `writer` -  The writer the settings are written to
+    /// 
+    pub fn write_to<W: Write>(&self, mut writer: W) -> StdResult<(), SettingsError> {
+        let text = self.document_text();
+        if let IoResult::Err(ioerror) = writer.write_all(text.as_bytes()).and_then(|_| writer.flush()) {
+            return StdResult::Err(self.writing_file_error(&self.path, ioerror));
+        }
+        StdResult::Ok(())
+    }
+
+
+    /// Generic method use to get section/key value
+    /// Generic type parameter has to implement FromStr & Display traits
+    /// Returns a SettingsValue structure containing the value associated with the section
+    /// and the key if both exist or a default value if not ad SettingsValue.error set with the 
+    /// relative error
+    /// error message language depends on new associated function used to crete Settings 
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     match settings.load("test_files/settings.ini") {
+    ///         Result::Ok(()) => {
+    ///             let bool_value = settings.get("GLOBAL", "bool_value", false);
+    ///             match bool_value.error {
+    ///                 None => assert_eq!(true, bool_value.value),
+    ///                 Some(error) => eprintln!("{}", error)
+    ///             }
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `T` -  A default generic value returned in case an error occurs
+    ///  
+    pub fn get<T: FromStr + Display>(&self, section_name: &str, key: &str, default_value: T) -> SettingsValue<T> where <T as FromStr>::Err: Debug {
+        match self.get_value(section_name, key) {
+            StdResult::Ok(value) => SettingsValue { value, error: None },
+            StdResult::Err(error) => SettingsValue { value: default_value, error: Some(error) }
+        }
+    }
+
+    /// Generic method use to get section/key value without a default value
+    /// Generic type parameter has to implement FromStr trait
+    /// Returns std::result::Result::Ok(T) containing the value associated with the section
+    /// and the key or std::result::Result::Err(SettingsError) with the same errors
+    /// returned by Settings::get
+    /// # Examples
+    /// ```
+    /// use rssettings::{Settings, SettingsError};
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "[LOG]\nlevel = high\n".parse().unwrap();
+    ///     match settings.get_value::<u8>("LOG", "level") {
+    ///         Result::Ok(level) => println!("level {}", level),
+    ///         Result::Err(SettingsError::Parsing { line, .. }) => eprintln!("bad level at line {}", line),
+    ///         Result::Err(error) => eprintln!("{}", error)
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  
+    pub fn get_value<T: FromStr>(&self, section_name: &str, key: &str) -> StdResult<T, SettingsError> where <T as FromStr>::Err: Debug {
+        if let Some(value) = self.value_override(section_name, key) {
+            let value = self.interpolate(section_name, key, 0, value)?;
+            return value.parse::<T>().map_err(|error| self.parsing_error(section_name, key, 0, format!("{:#?}", error)));
+        }
+        if let Some(section) = self.get_section(section_name) {
+            if let Some(key_value) = section.get_key_value(key) {
+                let value = self.interpolate(section_name, key, key_value.line_cnt, &key_value.value)?;
+                value.parse::<T>().map_err(|error| {
+                    self.parsing_error(section_name, key, key_value.line_cnt, format!("{:#?}", error))
+                })
+            } else {
+                StdResult::Err(self.key_not_found_error(section_name, key))
+            }
+        } else {
+            StdResult::Err(self.section_not_found_error(section_name))
+        }
+    }
+
+    /// Returns the value of a key as it is written in the settings file,
+    /// without interpolation (see Settings::with_env_interpolation) and ignoring
+    /// environment and command-line overrides (see Settings::with_env_overrides),
+    /// Settings::get_value::<String> returns the overriding value
+    /// Returns std::result::Result::Ok(&str) or std::result::Result::Err(SettingsError)
+    /// SettingsError::SectionNotFound and SettingsError::KeyNotFound as Settings::get
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "[LOG]\ndir = ${HOME}/logs\n".parse().unwrap();
+    ///     assert_eq!("${HOME}/logs", settings.get_raw("LOG", "dir").unwrap());
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  
+    pub fn get_raw(&self, section_name: &str, key: &str) -> StdResult<&str, SettingsError> {
+        match self.get_section(section_name) {
+            Some(section) => match section.get(key) {
+                Some(value) => StdResult::Ok(value),
+                None => StdResult::Err(self.key_not_found_error(section_name, key))
+            },
+            None => StdResult::Err(self.section_not_found_error(section_name))
+        }
+    }
+
+    /// Generic method use to get the elements of a list value
+    /// Generic type parameter has to implement FromStr trait
+    /// The elements are the value parts separated by 'separator' without their
+    /// leading and trailing spaces, an empty value is an empty list;
+    /// if the section holds an array (repeated "key[]" keys, see Settings::with_array_keys)
+    /// the elements are the values of the array keys and 'separator' is not used
+    /// Returns std::result::Result::Ok(Vec<T>) or std::result::Result::Err(SettingsError)
+    /// SettingsError::SectionNotFound and SettingsError::KeyNotFound as Settings::get,
+    /// SettingsError::ListElementParsing with the index of the first element that can not be parsed
+    /// # Examples
+    /// ```
+    /// use rssettings::{Settings, SettingsError};
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "[SERVERS]\nports = 80, 443, http\n".parse().unwrap();
+    ///     match settings.get_list::<u16>("SERVERS", "ports", ",") {
+    ///         Result::Ok(ports) => println!("ports {:?}", ports),
+    ///         Result::Err(SettingsError::ListElementParsing { index, .. }) => assert_eq!(2, index),
+    ///         Result::Err(error) => eprintln!("{}", error)
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `separator` -  A string slice that holds the separator of the elements
+    ///  
+    pub fn get_list<T: FromStr>(&self, section_name: &str, key: &str, separator: &str) -> StdResult<Vec<T>, SettingsError> where <T as FromStr>::Err: Debug {
+        let key = key.strip_suffix(ARRAY_TAG).unwrap_or(key);
+        let elements = match self.value_override(section_name, key) {
+            // an overriding value is always a separated list
+            Some(value) => Self::split_list(&self.interpolate(section_name, key, 0, value)?, separator, 0),
+            None => self.list_elements(section_name, key, separator)?
+        };
+        elements.into_iter().enumerate().map(|(index, (element, line))| {
+            element.parse::<T>().map_err(|error| {
+                self.list_element_parsing_error(section_name, key, index, line, format!("{:#?}", error))
+            })
+        }).collect()
+    }
+
+    // Returns the interpolated elements of a list value or of an array and their lines,
+    // see Settings::get_list
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key` -  A string slice that holds the name of the key inside the Section without []
+    //  * `separator` -  A string slice that holds the separator of the elements
+    fn list_elements(&self, section_name: &str, key: &str, separator: &str) -> StdResult<Vec<(String, usize)>, SettingsError> {
+        let section = match self.get_section(section_name) {
+            Some(section) => section,
+            None => return StdResult::Err(self.section_not_found_error(section_name))
+        };
+        let array_key = format!("{}{}", key, ARRAY_TAG);
+        if self.array_keys && section.get_key_value(&array_key).is_some() {
+            section.values.iter().filter(|key_value| self.name_matching.matches(&key_value.key, &array_key))
+                .map(|key_value| {
+                    let value = self.interpolate(section_name, key, key_value.line_cnt, &key_value.value)?;
+                    StdResult::Ok((value.into_owned(), key_value.line_cnt))
+                }).collect()
+        } else {
+            let key_value = match section.get_key_value(key) {
+                Some(key_value) => key_value,
+                None => return StdResult::Err(self.key_not_found_error(section_name, key))
+            };
+            let value = self.interpolate(section_name, key, key_value.line_cnt, &key_value.value)?;
+            StdResult::Ok(Self::split_list(&value, separator, key_value.line_cnt))
+        }
+    }
+
+    // Associated function that splits a list value in its elements,
+    // an empty value is an empty list
+    // Returns the elements without leading and trailing spaces and their line
+    //  * `value` -  A string slice that holds the list value
+    //  * `separator` -  A string slice that holds the separator of the elements
+    //  * `line` -  The settings file line of the value
+    fn split_list(value: &str, separator: &str, line: usize) -> Vec<(String, usize)> {
+        if value.is_empty() {
+            vec![]
+        } else if separator.is_empty() {
+            vec![(value.trim().to_string(), line)]
+        } else {
+            value.split(separator).map(|element| (element.trim().to_string(), line)).collect()
+        }
+    }
+
+    /// Builds the error returned when some keys of a section bound to a struct
+    /// are missing or invalid, used by the code generated by #[derive(SettingsSection)]
+    /// The message lists the messages of 'errors', with the line of the invalid keys
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `errors` -  The errors of the missing or invalid keys
+    #[doc(hidden)]
+    pub fn invalid_section_error(&self, section_name: &str, errors: Vec<SettingsError>) -> SettingsError {
+        let section = section_name.to_string();
+        let messages = errors.iter().map(|error| self.message_with_line(error)).collect::<Vec<String>>().join("; ");
+        let message = self.format_message(INVALID_SECTION_MESSAGE_IDX, vec![&section, &messages]);
+        SettingsError::InvalidSection { section, errors, message }
+    }
+
+    // Returns the message of an error about a value followed by the settings file
+    // line of the value, the message alone for the other errors
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `error` -  The error
+    fn message_with_line(&self, error: &SettingsError) -> String {
+        match error {
+            SettingsError::Parsing { line, message, .. } |
+            SettingsError::ListElementParsing { line, message, .. } |
+            SettingsError::UndefinedVariable { line, message, .. } |
+            SettingsError::UndefinedReference { line, message, .. } |
+            SettingsError::ValueOutOfRange { line, message, .. } |
+            SettingsError::PatternMismatch { line, message, .. } |
+            SettingsError::ValueNotAllowed { line, message, .. } |
+            SettingsError::UnknownSection { line, message, .. } |
+            SettingsError::UnknownKey { line, message, .. } if *line > 0 => {
+                self.format_message(AT_LINE_MESSAGE_IDX, vec![message, &line.to_string()])
+            },
+            error => error.message().to_string()
+        }
+    }
+
+
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     match settings.load("test_files/settings.ini") {
+    ///         Result::Ok(()) => {
+    ///             let orig_bool_value = settings.get("GLOBAL", "bool_value", false);
+    ///             if let Some(error) = orig_bool_value.error {
+    ///                 eprintln!("{}", error);
+    ///             } else {
+    ///                 settings.set("GLOBAL", "bool_value", false).unwrap_or_else(|error| {
+    ///                     eprintln!("{}", error);
+    ///                 });
+    ///                 let new_bool_value = settings.get("GLOBAL", "bool_value", true);
+    ///                 assert_ne!(orig_bool_value.value, new_bool_value.value);
+    ///                 settings.set("GLOBAL", "bool_value", true).unwrap_or_else(|error| {
+    ///                     eprintln!("{}", error);
+    ///                 });
+    ///             }
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `T` -  A new value for the key 
+    /// 
+    pub fn set<T: Display>(&mut self, section_name: &str, key: &str, value: T) -> StdResult<(), SettingsError> {
+        if let Some(section) = self.get_section_mut(section_name) {
+            if !section.set(key, value.to_string()) {
+                return StdResult::Err(self.key_not_found_error(section_name, key));
+            }
+            self.changes.push(Change::Set(section_name.to_string(), key.to_string(), value.to_string()));
+            self.dirty = true;
+            StdResult::Ok(())
+        } else {
+            StdResult::Err(self.section_not_found_error(section_name))
+        }
+    }
+
+    /// Sets the value of a key inserting the section and/or the key
+    /// if they do not exist yet, inserted section/key are written
+    /// in the settings file by Settings::save
+    /// Returns std::result::Result::Err(SettingsError::InvalidName) if the section or
+    /// the key name can not be written in a settings file (i.g. it is empty or contains tags)
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     settings.set_or_insert("LOG", "log_level", 3).unwrap_or_else(|error| {
+    ///         eprintln!("{}", error);
+    ///     });
+    ///     assert_eq!(3, settings.get("LOG", "log_level", 0).value);
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `T` -  A new value for the key 
+    /// 
+    pub fn set_or_insert<T: Display>(&mut self, section_name: &str, key: &str, value: T) -> StdResult<(), SettingsError> {
+        if !Self::is_valid_key(key) {
+            return StdResult::Err(self.invalid_name_error(key));
+        }
+        self.add_section(section_name)?;
+        let id = self.next_id;
+        let value = value.to_string();
+        self.changes.push(Change::Set(section_name.to_string(), key.to_string(), value.clone()));
+        self.dirty = true;
+        if let Some(section) = self.get_section_mut(section_name) {
+            if section.set(key, value.clone()) {
+                return StdResult::Ok(());
+            }
+        }
+        let source = self.insert_source(section_name);
+        if let Some(section) = self.get_section_mut(section_name) {
+            let _ = section.add(KeyValuePair::new(key.to_string(), value.clone(), (0, 0), id, source));
+        }
+        self.next_id += 1;
+        let continuation = self.multi_line_values.indented()
+            .then(|| format!("{}{}", self.document(source).eol(), CONTINUATION_INDENT));
+        self.insert_key_value_line(section_name, source, KeyValueLine::new(id, key, &value, continuation));
+        StdResult::Ok(())
+    }
+
+    /// Generic method used to set or insert a list value, the elements are written
+    /// in the style the key has been read in: an array of repeated "key[]" keys
+    /// if the section holds one (see Settings::with_array_keys), a single value
+    /// with the elements separated by 'separator' otherwise
+    /// A new key ending with [] is written as an array if arrays are enabled
+    /// Generic type parameter has to implement Display trait
+    /// Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError::InvalidName)
+    /// if the section or key name can not be written in a settings file
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new().with_array_keys(true);
+    ///     settings.set_list("SERVERS", "ports", &[80, 443], ", ").unwrap();
+    ///     settings.set_list("SERVERS", "host[]", &["alpha", "beta"], ",").unwrap();
+    ///     let mut buffer: Vec<u8> = vec![];
+    ///     settings.write_to(&mut buffer).unwrap();
+    ///     assert_eq!("[SERVERS]\nports = 80, 443\nhost[] = alpha\nhost[] = beta\n", String::from_utf8(buffer).unwrap());
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `elements` -  The elements of the list
+    ///  * `separator` -  A string slice that holds the separator of the elements
+    /// 
+    pub fn set_list<T: Display>(&mut self, section_name: &str, key: &str, elements: &[T], separator: &str) -> StdResult<(), SettingsError> {
+        let elements: Vec<String> = elements.iter().map(|element| element.to_string()).collect();
+        let plain_key = key.strip_suffix(ARRAY_TAG).unwrap_or(key);
+        let array_key = format!("{}{}", plain_key, ARRAY_TAG);
+        let array = self.array_keys && (key.ends_with(ARRAY_TAG) || self.key_exists(section_name, &array_key));
+        if !array {
+            return self.set_or_insert(section_name, key, elements.join(separator));
+        }
+        if !Self::is_valid_key(&array_key) {
+            return StdResult::Err(self.invalid_name_error(&array_key));
+        }
+        self.add_section(section_name)?;
+        self.set_array(section_name, &array_key, &elements);
+        self.changes.push(Change::SetList(section_name.to_string(), key.to_string(), elements, separator.to_string()));
+        self.dirty = true;
+        StdResult::Ok(())
+    }
+
+    /// Adds an empty section, written in the settings file by Settings::save
+    /// Returns std::result::Result::Ok(true) if the section has been added,
+    /// std::result::Result::Ok(false) if it already exists or
+    /// std::result::Result::Err(SettingsError::InvalidName) if the section name
+    /// can not be written in a settings file
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     assert_eq!(true, settings.add_section("LOG").unwrap());
+    ///     assert_eq!(false, settings.add_section("LOG").unwrap());
+    ///     assert!(settings.section_exists("LOG"));
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    /// 
+    pub fn add_section(&mut self, section_name: &str) -> StdResult<bool, SettingsError> {
+        if !Self::is_valid_section_name(section_name) {
+            return StdResult::Err(self.invalid_name_error(section_name));
+        }
+        if self.section_exists(section_name) {
+            return StdResult::Ok(false);
+        }
+        self.sections.push(Section::new(section_name, 0, self.name_matching));
+        Self::append_section_tag_line(&mut self.document, section_name);
+        self.changes.push(Change::AddSection(section_name.to_string()));
+        self.dirty = true;
+        StdResult::Ok(true)
+    }
+
+    /// Removes a key from a section, its line is deleted from the settings file
+    /// by Settings::save together with the comment lines right above it
+    /// if 'remove_comments' is true, all the elements of an array key
+    /// (i.g. "host[]", see Settings::with_array_keys) are removed
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     settings.set_or_insert("LOG", "log_level", 3).unwrap();
+    ///     assert!(settings.remove_key("LOG", "log_level", true).is_ok());
+    ///     assert!(!settings.key_exists("LOG", "log_level"));
+    ///     assert!(settings.remove_key("LOG", "log_level", true).is_err());
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    ///  * `remove_comments` -  If true the comment lines above the key are deleted too
+    /// 
+    pub fn remove_key(&mut self, section_name: &str, key: &str, remove_comments: bool) -> StdResult<(), SettingsError> {
+        if let Some(section) = self.get_section_mut(section_name) {
+            match section.remove(key) {
+                Some(key_value) => {
+                    let mut removed = vec![key_value];
+                    // all the elements of an array are removed
+                    if key.ends_with(ARRAY_TAG) {
+                        while let Some(key_value) = section.remove(key) {
+                            removed.push(key_value);
+                        }
+                    }
+                    for key_value in removed {
+                        let document = self.document_mut(key_value.source);
+                        if let Some(index) = document.position(key_value.id) {
+                            document.remove(index, remove_comments);
+                        }
+                    }
+                    self.changes.push(Change::RemoveKey(section_name.to_string(), key.to_string(), remove_comments));
+                    self.dirty = true;
+                    StdResult::Ok(())
+                },
+                None => StdResult::Err(self.key_not_found_error(section_name, key))
+            }
+        } else {
+            StdResult::Err(self.section_not_found_error(section_name))
+        }
+    }
+
+    /// Removes a section and all its keys, Settings::save deletes from the settings file
+    /// the section tag line and all the following lines up to the next section,
+    /// the comment lines right above the section tag are deleted too
+    /// if 'remove_comments' is true
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     settings.set_or_insert("LOG", "log_level", 3).unwrap();
+    ///     assert!(settings.remove_section("LOG", false).is_ok());
+    ///     assert!(!settings.section_exists("LOG"));
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `remove_comments` -  If true the comment lines above the section tag are deleted too
+    /// 
+    pub fn remove_section(&mut self, section_name: &str, remove_comments: bool) -> StdResult<(), SettingsError> {
+        match self.sections.iter().position(|section| self.name_matching.matches(&section.name, section_name)) {
+            Some(index) => {
+                let section = self.sections.remove(index);
+                self.remove_section_lines(&section, remove_comments);
+                self.changes.push(Change::RemoveSection(section_name.to_string(), remove_comments));
+                self.dirty = true;
+                StdResult::Ok(())
+            },
+            None => StdResult::Err(self.section_not_found_error(section_name))
+        }
+    }
+
+    /// Returns if a section exists
+    /// 
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     match settings.load("test_files/settings.ini") {
+    ///         Result::Ok(()) => {
+    ///             if settings.section_exists("GOOFY") {
+    ///                 println!("[GOOFY] exists"); 
+    ///             } else {
+    ///                 eprintln!("[GOOFY] does not exist");
+    ///             }
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    /// 
+    pub fn section_exists(&self, section_name: &str) -> bool {
+        self.get_section(section_name).is_some()
+    }
+
+    /// Returns if a key exists in a specific section
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     match settings.load("test_files/settings.ini") {
+    ///         Result::Ok(()) => {
+    ///             if settings.key_exists("GLOBAL", "enabled") {
+    ///                 println!("key 'enabled' in section 'GLOBAL' exists"); 
+    ///             } else {
+    ///                 eprintln!("key 'enabled' in section 'GLOBAL' does not exist"); 
+    ///             }
+    ///         },
+    ///         Result::Err(error) => {
+    ///             eprintln!("{}", error);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
     /// 
     pub fn key_exists(&self, section_name: &str, key: &str) -> bool {
         let mut result = false;
         if let Some(section) = self.get_section(section_name) {
-            if let Some(_) = section.get(key) {
-                result = true;
+            result = section.get(key).is_some();
+        }
+        result
+    }
+
+    /// Returns an iterator over the section names, in the order they have been
+    /// loaded or inserted and as they are written in the settings file
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "name = app\n[LOG]\nlevel = 1\n[DB]\nport = 5432\n".parse().unwrap();
+    ///     assert_eq!(vec!["GLOBAL", "LOG", "DB"], settings.sections().collect::<Vec<&str>>());
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn sections(&self) -> impl Iterator<Item = &str> + '_ {
+        self.sections.iter().map(|section| section.name.as_str())
+    }
+
+    /// Returns an iterator over the keys of a section in settings file order,
+    /// nothing if the section does not exist; an array key (see Settings::with_array_keys)
+    /// is returned once for each element
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "[LOG]\nenabled = true\nlevel = 1\n".parse().unwrap();
+    ///     assert_eq!(vec!["enabled", "level"], settings.keys("LOG").collect::<Vec<&str>>());
+    ///     assert_eq!(0, settings.keys("DB").count());
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    /// 
+    pub fn keys<'a>(&'a self, section_name: &str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries(section_name).map(|(key, _, _)| key)
+    }
+
+    /// Returns an iterator over key, value and settings file line of the key/value pairs
+    /// of a section, nothing if the section does not exist; values are returned as they are
+    /// held by Settings, not interpolated and without overrides (see Settings::apply_overrides),
+    /// the line is 0 for a key inserted and not yet saved
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "[LOG]\nenabled = true\nlevel = 1\n".parse().unwrap();
+    ///     for (key, value, line) in settings.entries("LOG") {
+    ///         println!("{} = {} at line {}", key, value, line);
+    ///     }
+    ///     assert_eq!(Some(("level", "1", 3)), settings.entries("LOG").last());
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    /// 
+    pub fn entries<'a>(&'a self, section_name: &str) -> impl Iterator<Item = (&'a str, &'a str, usize)> + 'a {
+        self.get_section(section_name).into_iter().flat_map(|section| section.values.iter())
+            .map(|key_value| (key_value.key.as_str(), key_value.value.as_str(), key_value.line_cnt))
+    }
+
+    /// Returns a copy of the sections and of their key/value pairs, values are copied
+    /// as Settings::entries returns them; every element of an array (see Settings::with_array_keys)
+    /// has its own key made of the array key and of the element index (i.g. "host[0]", "host[1]")
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let settings: Settings = "[LOG]\nenabled = true\nlevel = 1\n".parse().unwrap();
+    ///     let map = settings.to_map();
+    ///     assert_eq!(Some(&"1".to_string()), map["LOG"].get("level"));
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    /// 
+    pub fn to_map(&self) -> BTreeMap<String, BTreeMap<String, String>> {
+        let mut map = BTreeMap::new();
+        for section in &self.sections {
+            let mut values: BTreeMap<String, String> = BTreeMap::new();
+            let mut indexes: HashMap<&str, usize> = HashMap::new();
+            for key_value in &section.values {
+                match key_value.key.strip_suffix(ARRAY_TAG) {
+                    Some(array_key) if self.array_keys => {
+                        let index = indexes.entry(array_key).or_insert(0);
+                        values.insert(format!("{}[{}]", array_key, index), key_value.value.clone());
+                        *index += 1;
+                    },
+                    _ => {
+                        values.insert(key_value.key.clone(), key_value.value.clone());
+                    }
+                }
             }
+            map.insert(section.name.clone(), values);
+        }
+        map
+    }
+
+    /// Returns the range of settings file lines holding a key/value pair,
+    /// more than one line for a multi-line value (see Settings::with_multi_line_values)
+    /// Returns None if the key does not exist or it has been inserted and not yet saved
+    /// # Examples
+    /// ```
+    /// use rssettings::{Settings, MultiLineValues};
+    /// 
+    /// fn main() {
+    ///     let text = "[SQL]\nquery = SELECT * \\\n    FROM users\nlimit = 10\n";
+    ///     let mut settings = Settings::new().with_multi_line_values(MultiLineValues::Backslash);
+    ///     settings.load_from_reader(text.as_bytes(), "sql.ini").unwrap();
+    ///     assert_eq!(Some(2..=3), settings.key_lines("SQL", "query"));
+    ///     assert_eq!(Some(4..=4), settings.key_lines("SQL", "limit"));
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    /// 
+    pub fn key_lines(&self, section_name: &str, key: &str) -> Option<RangeInclusive<usize>> {
+        let key_value = self.get_section(section_name)?.get_key_value(key)?;
+        if key_value.line_cnt == 0 {
+            return None;
+        }
+        Some(key_value.line_cnt..=key_value.last_line_cnt)
+    }
+
+    /// Returns the path of the settings file holding a key/value pair: the loaded
+    /// settings file or the file included by an include directive (see Settings::load)
+    /// Returns None if the key does not exist
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// 
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     settings.load("test_files/include.ini").unwrap();
+    ///     assert_eq!(Some("test_files/include.ini"), settings.key_file("GLOBAL", "name"));
+    ///     assert_eq!(Some("test_files/include/local.ini"), settings.key_file("LOG", "level"));
+    /// }
+    /// ```
+    /// 
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `section_name` -  A string slice that holds the name of the Section
+    ///  * `key` -  A string slice that holds the name of the key inside the Section
+    /// 
+    pub fn key_file(&self, section_name: &str, key: &str) -> Option<&str> {
+        let key_value = self.get_section(section_name)?.get_key_value(key)?;
+        match key_value.source {
+            0 => Some(&self.path),
+            source => Some(&self.includes[source - 1].path)
         }
-        result
     }
 
 
@@ -611,100 +2113,871 @@ impl Settings {
 
     // This method is in charge to load the file passed to the public method load
     // Returns std::result::Result::Ok(()) in case file has been succesufuly loaded 
-    // otherwhise std::result::Result::Err(erroe: String) error contains the reason why 
+    // otherwhise std::result::Result::Err(error: SettingsError) error contains the reason why 
     // file has not been loaded
     //  * `self` -  A mutable reference to Setting struct
     //  * `path` -  A Path as reference to the settings file to load
-    fn load_private<P>(&mut self, path: P) -> StdResult<(), String> where P: AsRef<Path> {
+    fn load_private<P>(&mut self, path: P) -> StdResult<(), SettingsError> where P: AsRef<Path> {
+        let path_str = path.as_ref().as_os_str().to_str().unwrap_or("");
+        let (content, modified) = self.read_file(path.as_ref(), path_str)?;
+        let mut include_stack = vec![fs::canonicalize(path.as_ref()).unwrap_or_else(|_| path.as_ref().to_path_buf())];
+        self.read_document(content.as_slice(), path_str, 0, Some(path.as_ref()), &mut include_stack)?;
 
+        self.file_stamp = Some(FileStamp::new(&content, modified));
+        self.path = path_str.to_string();
+        self.file = Some(path.as_ref().to_path_buf());
+        self.initialized = true;
+        StdResult::Ok(())
+    }
 
-        let path_str = path.as_ref().as_os_str().to_str().unwrap_or("");
-        match File::open(path_str) {
-            IoResult::Ok(settings_file) => {
-                let lines = io::BufReader::new(settings_file).lines();
-                let mut line_cnt = 1usize;
-                let mut current_section = String::from(GLOBAL_SECTION);
-                for line in lines {
-                    match line {
-                        IoResult::Ok(line_text) => {
-                            match self.line_type(&line_text, &line_cnt, path_str) {
-                                LineType::SectionLine(section_name) => {
-                                    if current_section != section_name {
-                                        current_section = section_name;
-                                    }
-                                },
-                                LineType::KeyAndValue(key, value) => {
-                                    self.add_to_section(&current_section, key, value, line_cnt.clone(), path_str)?;
-                                },
-                                LineType::BadFormattedLine(error) => {
-                                    return StdResult::Err(error);
-                                },
-                                LineType::EmptyLine => {
-
-                                }
+    // Reads the content of a settings file
+    // Returns std::result::Result::Ok((content, modification time)) or
+    // std::result::Result::Err(error: SettingsError) if the file can not be opened or read
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `file` -  The path of the file to read
+    //  * `path_str` -  The file path used in error messages
+    fn read_file(&self, file: &Path, path_str: &str) -> StdResult<(Vec<u8>, Option<SystemTime>), SettingsError> {
+        let mut content = vec![];
+        match File::open(file) {
+            IoResult::Ok(mut settings_file) => {
+                let modified = settings_file.metadata().and_then(|metadata| metadata.modified()).ok();
+                if let IoResult::Err(ioerror) = settings_file.read_to_end(&mut content) {
+                    return StdResult::Err(self.reading_file_error(path_str, 1, ioerror));
+                }
+                StdResult::Ok((content, modified))
+            },
+            IoResult::Err(ioerror) => {
+                StdResult::Err(self.opening_file_error(path_str, ioerror))
+            }
+        }
+    }
+
+    // Loads a file included by an include directive, its key/value pairs
+    // are added to the sections in the order they are read
+    // Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError::IncludeCycle)
+    // if the file is already being loaded, the loading error otherwise
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `file` -  The path of the included file
+    //  * `include_stack` -  The canonical paths of the files being loaded, the settings file first
+    //  * `line_cnt` -  The line of the include directive
+    //  * `including_path` -  The path of the including file used in error messages
+    fn load_include(&mut self, file: &Path, include_stack: &mut Vec<PathBuf>, line_cnt: usize, including_path: &str) -> StdResult<(), SettingsError> {
+        let path_str = file.as_os_str().to_str().unwrap_or("").to_string();
+        let canonical = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        if include_stack.contains(&canonical) {
+            let line = format!("{}", line_cnt);
+            let path = including_path.to_string();
+            let message = self.format_message(INCLUDE_CYCLE_MESSAGE_IDX, vec![&line, &path, &path_str]);
+            return StdResult::Err(SettingsError::IncludeCycle { line: line_cnt, path, include: path_str, message });
+        }
+        let (content, modified) = self.read_file(file, &path_str)?;
+        self.includes.push(IncludedFile {
+            file: file.to_path_buf(),
+            path: path_str.clone(),
+            document: Document::default(),
+            file_stamp: Some(FileStamp::new(&content, modified))
+        });
+        let source = self.includes.len();
+        include_stack.push(canonical);
+        self.read_document(content.as_slice(), &path_str, source, Some(file), include_stack)?;
+        include_stack.pop();
+        StdResult::Ok(())
+    }
+
+    // Loads the files included by an include directive line: the file of !include
+    // or the files of the directory of !includedir sorted by name, hidden files excluded
+    // Returns std::result::Result::Ok(false) if the line is not an include directive,
+    // std::result::Result::Ok(true) once the files have been loaded or the loading error
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `line_text` -  The line text
+    //  * `line_cnt` -  The line number
+    //  * `file` -  The path of the including file, relative included paths start from its directory
+    //  * `path_str` -  The path of the including file used in error messages
+    //  * `include_stack` -  The canonical paths of the files being loaded
+    fn load_directive(&mut self, line_text: &str, line_cnt: usize, file: &Path, path_str: &str, include_stack: &mut Vec<PathBuf>) -> StdResult<bool, SettingsError> {
+        let directive = line_text.trim();
+        let (included, is_directory) = if let Some(directory) = Self::directive_argument(directive, INCLUDE_DIR_DIRECTIVE) {
+            (directory, true)
+        } else if let Some(included_file) = Self::directive_argument(directive, INCLUDE_DIRECTIVE) {
+            (included_file, false)
+        } else {
+            return StdResult::Ok(false);
+        };
+        let included = match file.parent() {
+            Some(directory) => directory.join(included),
+            None => PathBuf::from(included)
+        };
+        if !is_directory {
+            self.load_include(&included, include_stack, line_cnt, path_str)?;
+            return StdResult::Ok(true);
+        }
+        let entries = match fs::read_dir(&included) {
+            IoResult::Ok(entries) => entries,
+            IoResult::Err(ioerror) => {
+                return StdResult::Err(self.opening_file_error(included.to_str().unwrap_or(""), ioerror));
+            }
+        };
+        let mut files: Vec<PathBuf> = entries.filter_map(|entry| entry.ok())
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| !file_type.is_dir()))
+            .map(|entry| entry.path())
+            .collect();
+        files.sort();
+        for included_file in files {
+            self.load_include(&included_file, include_stack, line_cnt, path_str)?;
+        }
+        StdResult::Ok(true)
+    }
+
+    // Returns the argument of an include directive, None if the line is not the directive
+    //  * `directive` -  The trimmed line text
+    //  * `name` -  The directive name
+    fn directive_argument<'a>(directive: &'a str, name: &str) -> Option<&'a str> {
+        let argument = directive.strip_prefix(name)?;
+        if !argument.starts_with(char::is_whitespace) {
+            return None;
+        }
+        Some(argument.trim()).filter(|argument| !argument.is_empty())
+    }
+
+    // This method reads the settings lines building sections and document
+    // Returns std::result::Result::Ok(()) in case settings have been succesufuly read
+    // otherwhise std::result::Result::Err(error: SettingsError) error contains the reason why 
+    // settings have not been read
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `reader` -  The reader of the settings lines
+    //  * `path_str` -  The settings file path or source name used in error messages
+    //  * `source` -  The source of the key/value pairs, see KeyValuePair
+    //  * `file` -  The path of the settings file, None if it is read from a reader
+    //     that can not have include directives
+    //  * `include_stack` -  The canonical paths of the files being loaded
+    fn read_document<R: BufRead>(&mut self, reader: R, path_str: &str, source: usize, file: Option<&Path>, include_stack: &mut Vec<PathBuf>) -> StdResult<(), SettingsError> {
+        let lines = self.read_lines(reader, path_str)?;
+        let mut document = Document::default();
+        let mut current_section = String::from(GLOBAL_SECTION);
+        let mut index = 0;
+        while index < lines.len() {
+            let (line_text, eol) = &lines[index];
+            let line_cnt = index + 1;
+            index += 1;
+            if let Some(file) = file {
+                if self.load_directive(line_text, line_cnt, file, path_str, include_stack)? {
+                    document.push(LineKind::Directive(line_text.clone()), eol);
+                    continue;
+                }
+            }
+            match self.line_type(line_text, &line_cnt, path_str) {
+                LineType::SectionLine(section_name) => {
+                    if !self.section_exists(&section_name) {
+                        self.sections.push(Section::new(&section_name, line_cnt, self.name_matching));
+                    }
+                    if current_section != section_name {
+                        current_section = section_name.clone();
+                    }
+                    document.push(LineKind::SectionTag(section_name, line_text.clone()), eol);
+                },
+                LineType::KeyAndValue(key, mut key_value_line) => {
+                    let mut eol = eol;
+                    if !key_value_line.raw_value.starts_with(document::QUOTES) {
+                        while let Some((next_text, next_eol)) = lines.get(index) {
+                            if !self.continue_value(&mut key_value_line, eol, next_text) {
+                                break;
                             }
-                        },
-                        IoResult::Err(ioerror) => {
-                            let error = format!("{:#}", ioerror);
-                            let line = format!("{}", line_cnt);
-                            return StdResult::Err(self.format_message(READING_FILE_ERROR_MESSAGE_IDX, 
-                                vec![&path_str.to_string(), &line, &error]));            
+                            eol = next_eol;
+                            index += 1;
+                        }
+                        if self.multi_line_values.indented() && key_value_line.continuation.is_none() {
+                            let continuation_eol = if eol.is_empty() { "\n" } else { eol.as_str() };
+                            key_value_line.continuation = Some(format!("{}{}", continuation_eol, CONTINUATION_INDENT));
                         }
                     }
-                    line_cnt = line_cnt + 1;
+                    key_value_line.id = self.next_id;
+                    self.next_id += 1;
+                    let key_value = KeyValuePair::new(key, key_value_line.value.clone(), (line_cnt, index), key_value_line.id, source);
+                    self.add_to_section(&current_section, key_value, path_str)?;
+                    document.push(LineKind::KeyValue(key_value_line), eol);
+                },
+                LineType::BadFormattedLine(error) => {
+                    return StdResult::Err(error);
+                },
+                LineType::EmptyLine => {
+                    document.push(LineKind::Trivia(line_text.clone()), eol);
                 }
-            },
-            IoResult::Err(ioerror) => {
-                let error = format!("{:#}", ioerror);
-                return StdResult::Err(self.format_message(OPENING_FILE_ERROR_MESSAGE_IDX,
-                    vec![&path_str.to_string(), &error]));
             }
+        }
+        *self.document_mut(source) = document;
+        StdResult::Ok(())
+    }
 
+    // Reads all the settings lines
+    // Returns std::result::Result::Ok(lines) with the text and the line ending of every line
+    // or std::result::Result::Err(SettingsError::ReadingFile) if a line can not be read
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `reader` -  The reader of the settings lines
+    //  * `path_str` -  The settings file path or source name used in error messages
+    fn read_lines<R: BufRead>(&self, mut reader: R, path_str: &str) -> StdResult<Vec<(String, String)>, SettingsError> {
+        let mut lines = vec![];
+        loop {
+            let mut line_text = String::new();
+            match reader.read_line(&mut line_text) {
+                IoResult::Ok(0) => {
+                    return StdResult::Ok(lines);
+                },
+                IoResult::Ok(_) => {
+                    let eol = Self::split_eol(&mut line_text);
+                    lines.push((line_text, eol));
+                },
+                IoResult::Err(ioerror) => {
+                    return StdResult::Err(self.reading_file_error(path_str, lines.len() + 1, ioerror));
+                }
+            }
         }
+    }
 
-        self.path = path_str.to_string();
+    // Appends to an unquoted value the next line if it continues the value
+    // according to the multi-line values setting (see Settings::with_multi_line_values)
+    // Returns true if the next line has been appended
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `key_value_line` -  The tokens of the key/value line read so far
+    //  * `eol` -  The line ending of the last line of the key/value line
+    //  * `next_text` -  The text of the next line
+    fn continue_value(&self, key_value_line: &mut KeyValueLine, eol: &str, next_text: &str) -> bool {
+        let part = if self.multi_line_values.backslash() && key_value_line.value.ends_with('\\') {
+            let content_end = next_text.find(COMMENT_TAG).unwrap_or(next_text.len());
+            let part = next_text[..content_end].trim_end();
+            key_value_line.value.pop();
+            key_value_line.value.push_str(part.trim_start());
+            part
+        } else if self.multi_line_values.indented() && next_text.starts_with(char::is_whitespace) && !next_text.trim().is_empty() {
+            let part = next_text.trim_end();
+            if key_value_line.continuation.is_none() {
+                let indent = &part[..part.len() - part.trim_start().len()];
+                key_value_line.continuation = Some(format!("{}{}", eol, indent));
+            }
+            key_value_line.value.push('\n');
+            key_value_line.value.push_str(part.trim_start());
+            part
+        } else {
+            return false;
+        };
+        // the value text spans from the first line value up to the end of the appended one
+        key_value_line.raw_value = format!("{}{}{}{}", key_value_line.raw_value, key_value_line.suffix, eol, part);
+        key_value_line.suffix = next_text[part.len()..].to_string();
+        true
+    }
+
+    // This method writes the document in a file
+    // Returns std::result::Result::Ok(()) in case file has been succesufuly written
+    // otherwhise std::result::Result::Err(error: SettingsError)
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `file` -  The path of the file to write
+    //  * `path_str` -  The file path used in error messages
+    fn save_private(&mut self, file: &Path, path_str: &str) -> StdResult<(), SettingsError> {
+        // included files are written first: if one of them fails the settings file
+        // is not replaced and the next save writes again what has not been written
+        self.save_includes()?;
+        let text = self.document_text();
+        if let IoResult::Err(ioerror) = Self::write_atomically(file, text.as_bytes()) {
+            return StdResult::Err(self.writing_file_error(path_str, ioerror));
+        }
+        self.commit_document();
+        self.update_line_numbers();
+        let modified = fs::metadata(file).and_then(|metadata| metadata.modified()).ok();
+        self.file_stamp = Some(FileStamp::new(text.as_bytes(), modified));
+        self.changes.clear();
+        self.dirty = false;
+        StdResult::Ok(())
+    }
+
+    // Writes the included files whose text changed, the unchanged ones are not touched
+    // Returns std::result::Result::Ok(()) in case files have been succesufuly written
+    // otherwhise std::result::Result::Err(error: SettingsError)
+    //  * `self` -  A mutable reference to Setting struct
+    fn save_includes(&mut self) -> StdResult<(), SettingsError> {
+        let values = self.current_values();
+        let changed: Vec<(usize, String)> = self.includes.iter().enumerate()
+            .map(|(index, include)| (index, include.document.text(&values)))
+            .filter(|(index, text)| {
+                let text_stamp = FileStamp::new(text.as_bytes(), None);
+                !self.includes[*index].file_stamp.as_ref()
+                    .is_some_and(|file_stamp| file_stamp.len == text_stamp.len && file_stamp.hash == text_stamp.hash)
+            })
+            .collect();
+        for (index, text) in changed {
+            let include = &self.includes[index];
+            if let IoResult::Err(ioerror) = Self::write_atomically(&include.file, text.as_bytes()) {
+                return StdResult::Err(self.writing_file_error(&include.path, ioerror));
+            }
+            let modified = fs::metadata(&include.file).and_then(|metadata| metadata.modified()).ok();
+            self.includes[index].file_stamp = Some(FileStamp::new(text.as_bytes(), modified));
+        }
+        StdResult::Ok(())
+    }
+
+    // Returns the path of the first file, the settings file or an included one,
+    // whose content is not the one loaded or saved, None if no file has been modified
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `file` -  The path of the settings file
+    fn modified_file_path(&self, file: &Path) -> Option<String> {
+        if Self::is_file_modified(&self.file_stamp, file) {
+            return Some(self.path.clone());
+        }
+        self.includes.iter()
+            .find(|include| Self::is_file_modified(&include.file_stamp, &include.file))
+            .map(|include| include.path.clone())
+    }
+
+    // Associated function that returns true if a file content is not the one
+    // loaded or saved, the file is read only if its modification time or length changed
+    // A missing or unreadable file is not considered modified, Settings::save reports
+    // the error writing it
+    //  * `file_stamp` -  The stamp of the file content as it was loaded or saved
+    //  * `file` -  The path of the file
+    fn is_file_modified(file_stamp: &Option<FileStamp>, file: &Path) -> bool {
+        let file_stamp = match file_stamp {
+            Some(file_stamp) => file_stamp,
+            None => return false
+        };
+        let metadata = match fs::metadata(file) {
+            IoResult::Ok(metadata) => metadata,
+            IoResult::Err(_) => return false
+        };
+        let modified = metadata.modified().ok();
+        if metadata.len() == file_stamp.len && modified.is_some() && modified == file_stamp.modified {
+            return false;
+        }
+        match fs::read(file) {
+            IoResult::Ok(content) => {
+                let current_stamp = FileStamp::new(&content, file_stamp.modified);
+                current_stamp != *file_stamp
+            },
+            IoResult::Err(_) => false
+        }
+    }
+
+    // Returns the path of the file written by Settings::save, None if Settings
+    // has been loaded from a reader or a string
+    //  * `self` -  An immutable reference to Setting struct
+    pub(crate) fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    // Returns true if the settings file or one of its included files
+    // has been modified by someone else since it has been loaded or saved
+    //  * `self` -  An immutable reference to Setting struct
+    pub(crate) fn is_modified_on_disk(&self) -> bool {
+        match &self.file {
+            Some(file) => self.modified_file_path(file).is_some(),
+            None => false
+        }
+    }
+
+    // Returns section name, key and value of all the key/value pairs
+    // in sections and file order
+    //  * `self` -  An immutable reference to Setting struct
+    pub(crate) fn key_values(&self) -> Vec<(String, String, String)> {
+        self.sections.iter()
+            .flat_map(|section| section.values.iter()
+                .map(move |key_value| (section.name.clone(), key_value.key.clone(), key_value.value.clone())))
+            .collect()
+    }
+
+    // Loads the settings file in a new Settings using the same messages table
+    // Returns the new Settings or the loading error
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `file` -  The path of the settings file
+    fn load_again(&self, file: &Path) -> StdResult<Settings, SettingsError> {
+        let mut loaded = Settings::new().with_drop_policy(DropPolicy::Never)
+            .with_multi_line_values(self.multi_line_values)
+            .with_array_keys(self.array_keys)
+            .with_name_matching(self.name_matching);
+        loaded.messages_table = self.messages_table.clone();
+        loaded.load_private(file)?;
+        StdResult::Ok(loaded)
+    }
+
+    // Replaces the content of Settings (sections, document and file stamp)
+    // with the one of another Settings
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `other` -  A mutable reference to the Settings giving its content
+    fn take_content(&mut self, other: &mut Settings) {
+        std::mem::swap(&mut self.sections, &mut other.sections);
+        std::mem::swap(&mut self.document, &mut other.document);
+        std::mem::swap(&mut self.includes, &mut other.includes);
+        std::mem::swap(&mut self.file_stamp, &mut other.file_stamp);
+        self.next_id = other.next_id;
+    }
+
+    // Loads again the modified settings file and applies to it the changes
+    // made to Settings since it has been loaded or saved
+    // Returns std::result::Result::Ok(()) in case of success,
+    // std::result::Result::Err(error: SettingsError) if the file can not be loaded or
+    // std::result::Result::Err(SettingsError::Conflict) if a change can not be applied,
+    // Settings is not changed when an error is returned
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `file` -  The path of the settings file
+    //  * `path` -  The path of the settings file used in the error message
+    fn merge(&mut self, file: &Path, path: String) -> StdResult<(), SettingsError> {
+        let mut loaded = self.load_again(file)?;
+        for change in &self.changes {
+            let result = match change {
+                Change::Set(section_name, key, value) => loaded.set_or_insert(section_name, key, value),
+                Change::AddSection(section_name) => loaded.add_section(section_name).map(|_| ()),
+                Change::RemoveKey(section_name, key, remove_comments) => loaded.remove_key(section_name, key, *remove_comments),
+                Change::RemoveSection(section_name, remove_comments) => loaded.remove_section(section_name, *remove_comments),
+                Change::SetList(section_name, key, elements, separator) => loaded.set_list(section_name, key, elements, separator)
+            };
+            match result {
+                StdResult::Ok(()) => {},
+                // a section/key removed by the other side too is already removed
+                StdResult::Err(SettingsError::SectionNotFound { .. } | SettingsError::KeyNotFound { .. })
+                    if matches!(change, Change::RemoveKey(..) | Change::RemoveSection(..)) => {},
+                // the change would be lost, saving fails as with ConflictPolicy::Fail
+                StdResult::Err(_) => {
+                    let message = self.format_message(CONFLICT_MESSAGE_IDX, vec![&path]);
+                    return StdResult::Err(SettingsError::Conflict { path, message });
+                }
+            }
+        }
+        self.take_content(&mut loaded);
         StdResult::Ok(())
     }
 
+    // Associated function that replaces the content of a file in a crash safe way:
+    // the content is written and synced to a temporary file in the same directory,
+    // unique for every call and taking the permissions of the original file
+    // before the content is written, and then the temporary file
+    // is renamed over the original one, so the file is either the old or the new one
+    // and never a truncated one. If the file is a symbolic link its target is replaced.
+    // Returns the io error of the first failed operation, the temporary file is removed
+    //  * `file` -  The path of the file to write
+    //  * `content` -  The bytes to write
+    fn write_atomically(file: &Path, content: &[u8]) -> IoResult<()> {
+        Self::write_through_temp_file(file, content, true)
+    }
+
+    // Associated function writing a file through a temporary file,
+    // see Settings::write_atomically and Settings::create_atomically (schema feature)
+    //  * `file` -  The path of the file to write
+    //  * `content` -  The bytes to write
+    //  * `replace` -  If true the temporary file is renamed over the file,
+    //                 otherwise it is hard linked to the file and removed
+    fn write_through_temp_file(file: &Path, content: &[u8], replace: bool) -> IoResult<()> {
+        let target = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        let directory = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from(".")
+        };
+        let file_name = match target.file_name() {
+            Some(file_name) => file_name.to_string_lossy().to_string(),
+            None => return IoResult::Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))
+        };
+        let counter = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp_path = directory.join(format!(".{}.{}.{}.tmp", file_name, std::process::id(), counter));
+
+        let result = File::options().write(true).create_new(true).open(&temp_path).and_then(|mut temp_file| {
+            if let IoResult::Ok(metadata) = fs::metadata(&target) {
+                temp_file.set_permissions(metadata.permissions())?;
+            }
+            temp_file.write_all(content)?;
+            temp_file.sync_all()?;
+            if replace {
+                fs::rename(&temp_path, &target)
+            } else {
+                fs::hard_link(&temp_path, &target)
+            }
+        });
+        match result {
+            IoResult::Ok(()) => {
+                if !replace {
+                    let _ = fs::remove_file(&temp_path);
+                }
+                // make the rename durable, not every platform allows to sync a directory
+                if let IoResult::Ok(directory) = File::open(&directory) {
+                    let _ = directory.sync_all();
+                }
+                IoResult::Ok(())
+            },
+            IoResult::Err(ioerror) => {
+                let _ = fs::remove_file(&temp_path);
+                IoResult::Err(ioerror)
+            }
+        }
+    }
+
     // This method is privatly used to clean Setting stucture content
     // it is used when the load methos fails
     //  * `self` -  A mutable reference to Setting struct
     fn unload(&mut self) {
-        let mut iter = self.sections.iter_mut();
-        while let Some(section) = iter.next() {
+        for section in &mut self.sections {
             section.unload();
         }
-        self.sections.clear();
+        self.sections.clear();
+        self.document = Document::default();
+        self.includes.clear();
+        self.initialized = false;
+        self.dirty = false;
+        self.file_stamp = None;
+        self.changes.clear();
+    }
+
+
+    // This method format the error message string in base to the message index
+    // and parameters passed as a vector of string reference
+    // Returns the formatted error message
+    // in the message placeholders '{}'
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `message_idx` -  Message index to format
+    //  * `params` -  A vector od String reference to format the message
+    fn format_message(&self, message_idx: usize, params: Vec<&String>) -> String {
+        let mut message = self.messages_table[message_idx].clone();
+        let mut i = 0usize;
+        while message.contains("{}") {
+            message = message.replacen("{}", params[i], 1);
+            i += 1;
+            if i >= params.len() {
+                break;
+            }
+        }
+
+        message
+    }
+
+    // The following methods build the SettingsError variants
+    // shared by more than one method formatting their message
+    //  * `self` -  An immutable reference to Setting struct
+    fn opening_file_error(&self, path: &str, source: io::Error) -> SettingsError {
+        let path = path.to_string();
+        let error = format!("{:#}", source);
+        let message = self.format_message(OPENING_FILE_ERROR_MESSAGE_IDX, vec![&path, &error]);
+        SettingsError::OpeningFile { path, source, message }
+    }
+
+    fn reading_file_error(&self, path: &str, line_cnt: usize, source: io::Error) -> SettingsError {
+        let path = path.to_string();
+        let error = format!("{:#}", source);
+        let line = format!("{}", line_cnt);
+        let message = self.format_message(READING_FILE_ERROR_MESSAGE_IDX, vec![&path, &line, &error]);
+        SettingsError::ReadingFile { path, line: line_cnt, source, message }
+    }
+
+    fn writing_file_error(&self, path: &str, source: io::Error) -> SettingsError {
+        let path = path.to_string();
+        let error = format!("{:#}", source);
+        let message = self.format_message(WRITING_FILE_ERROR_MESSAGE_IDX, vec![&path, &error]);
+        SettingsError::WritingFile { path, source, message }
+    }
+
+    fn section_not_found_error(&self, section_name: &str) -> SettingsError {
+        let section = section_name.to_string();
+        let message = self.format_message(SECTION_NOT_FOUND_MESSAGE_IDX, vec![&section]);
+        SettingsError::SectionNotFound { section, message }
+    }
+
+    fn key_not_found_error(&self, section_name: &str, key: &str) -> SettingsError {
+        let section = section_name.to_string();
+        let key = key.to_string();
+        let message = self.format_message(KEY_NOT_FOUND_MESSAGE_IDX, vec![&section, &key]);
+        SettingsError::KeyNotFound { section, key, message }
+    }
+
+    fn parsing_error(&self, section_name: &str, key: &str, line: usize, error: String) -> SettingsError {
+        let section = section_name.to_string();
+        let key = key.to_string();
+        let message = self.format_message(PARSING_ERROR_MESSAGE_IDX, vec![&section, &key, &error]);
+        SettingsError::Parsing { section, key, line, source: error, message }
+    }
+
+    fn list_element_parsing_error(&self, section_name: &str, key: &str, index: usize, line: usize, error: String) -> SettingsError {
+        let section = section_name.to_string();
+        let key = key.to_string();
+        let message = self.format_message(LIST_ELEMENT_PARSING_MESSAGE_IDX, vec![&section, &key, &index.to_string(), &error]);
+        SettingsError::ListElementParsing { section, key, index, line, source: error, message }
+    }
+
+    #[cfg(feature = "serde")]
+    fn serde_error(&self, source: String) -> SettingsError {
+        let message = self.format_message(SERDE_MESSAGE_IDX, vec![&source]);
+        SettingsError::Serde { source, message }
+    }
+
+    fn invalid_name_error(&self, name: &str) -> SettingsError {
+        let name = name.to_string();
+        let message = self.format_message(INVALID_NAME_MESSAGE_IDX, vec![&name]);
+        SettingsError::InvalidName { name, message }
+    }
+
+    // Returns if a section name can be written between section tags
+    // and read back unchanged
+    //  * `section_name` -  A string slice that holds the name of the Section
+    fn is_valid_section_name(section_name: &str) -> bool {
+        !section_name.is_empty() && section_name.trim() == section_name &&
+            !section_name.contains(['\n', '\r']) &&
+            !section_name.contains(COMMENT_TAG) &&
+            !section_name.contains(START_SECTION_TAG) &&
+            !section_name.contains(END_SECTION_TAG)
+    }
+
+    // Returns if a key can be written in front of the assign tag
+    // and read back unchanged
+    //  * `key` -  A string slice that holds the name of the key
+    fn is_valid_key(key: &str) -> bool {
+        !key.is_empty() && key.trim() == key &&
+            !key.contains(['\n', '\r']) &&
+            !key.contains(COMMENT_TAG) &&
+            !key.contains(ASSIGN_TAG) &&
+            !key.starts_with(START_SECTION_TAG)
+    }
+
+    // Removes the line ending from a line read from the settings file
+    // Returns the removed line ending
+    //  * `line_text` -  The line read from the settings file
+    fn split_eol(line_text: &mut String) -> String {
+        let mut eol = String::new();
+        if line_text.ends_with('\n') {
+            line_text.pop();
+            eol.push('\n');
+            if line_text.ends_with('\r') {
+                line_text.pop();
+                eol.insert(0, '\r');
+            }
+        }
+        eol
+    }
+
+    // Returns the settings file text to save, written from the document
+    // with the current values of the key/value pairs
+    //  * `self` -  An immutable reference to Setting struct
+    fn document_text(&self) -> String {
+        self.document.text(&self.current_values())
+    }
+
+    // Makes the current values of the key/value pairs the original values
+    // of the document, called once the document has been saved
+    //  * `self` -  A mutable reference to Setting struct
+    fn commit_document(&mut self) {
+        let values: HashMap<usize, &String> = self.sections.iter()
+            .flat_map(|section| section.values.iter())
+            .map(|key_value| (key_value.id, &key_value.value))
+            .collect();
+        self.document.commit(&values);
+        for include in &mut self.includes {
+            include.document.commit(&values);
+        }
+    }
+
+    // Returns a map from key/value pair ids to their current values
+    //  * `self` -  An immutable reference to Setting struct
+    fn current_values(&self) -> HashMap<usize, &String> {
+        self.sections.iter()
+            .flat_map(|section| section.values.iter())
+            .map(|key_value| (key_value.id, &key_value.value))
+            .collect()
+    }
+
+    // Updates sections and key/value pairs line numbers
+    // after the document has been written in the settings file
+    //  * `self` -  A mutable reference to Setting struct
+    fn update_line_numbers(&mut self) {
+        let mut key_value_lines: HashMap<usize, (usize, usize)> = HashMap::new();
+        let mut section_lines: HashMap<String, usize> = HashMap::new();
+        // line numbers are counted in the file of every line
+        let documents = std::iter::once(&self.document).chain(self.includes.iter().map(|include| &include.document));
+        for document in documents {
+            let mut line_cnt = 1usize;
+            for line in &document.lines {
+                let lines_count = line.lines_count();
+                if let Some(id) = line.key_value_id() {
+                    key_value_lines.insert(id, (line_cnt, line_cnt + lines_count - 1));
+                } else if let Some(section_name) = line.section_name() {
+                    section_lines.entry(section_name.to_string()).or_insert(line_cnt);
+                }
+                line_cnt += lines_count;
+            }
+        }
+        for section in &mut self.sections {
+            section.line_cnt = section_lines.get(&section.name).copied().unwrap_or(0);
+            for key_value in &mut section.values {
+                (key_value.line_cnt, key_value.last_line_cnt) = key_value_lines.get(&key_value.id).copied().unwrap_or((0, 0));
+            }
+        }
+    }
+
+    // Returns the document position following the last section tag or
+    // key/value line of a section, None if the section has no lines
+    //  * `document` -  The document of the settings file or of an included file
+    //  * `section_name` - A string slice that holds the name of the Section
+    //  * `name_matching` - How section names are compared
+    fn section_end(document: &Document, section_name: &str, name_matching: NameMatching) -> Option<usize> {
+        let mut current_section = GLOBAL_SECTION;
+        let mut end = None;
+        for (index, line) in document.lines.iter().enumerate() {
+            if let Some(name) = line.section_name() {
+                current_section = name;
+            } else if line.key_value_id().is_none() {
+                continue;
+            }
+            if name_matching.matches(current_section, section_name) {
+                end = Some(index + 1);
+            }
+        }
+        end
+    }
+
+    // Appends to a document the tag line of a new section
+    // separated by an empty line from the previous content
+    //  * `document` -  The document of the settings file or of an included file
+    //  * `section_name` - A string slice that holds the name of the Section
+    fn append_section_tag_line(document: &mut Document, section_name: &str) {
+        if document.lines.last().is_some_and(|line| !line.is_empty()) {
+            document.insert(document.lines.len(), LineKind::Trivia(String::new()));
+        }
+        let tag_line = format!("{}{}{}", START_SECTION_TAG, section_name, END_SECTION_TAG);
+        document.insert(document.lines.len(), LineKind::SectionTag(section_name.to_string(), tag_line));
+    }
+
+    // Sets the elements of an array: the existing array keys take the new values,
+    // the surplus ones are removed and the missing ones are inserted after the last one
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `section_name` - A string slice that holds the name of the existing Section
+    //  * `array_key` - A string slice that holds the array key, [] included
+    //  * `elements` - The new elements of the array
+    fn set_array(&mut self, section_name: &str, array_key: &str, elements: &[String]) {
+        let (ids, sources): (Vec<usize>, Vec<usize>) = match self.get_section(section_name) {
+            Some(section) => section.values.iter().filter(|key_value| self.name_matching.matches(&key_value.key, array_key))
+                .map(|key_value| (key_value.id, key_value.source)).unzip(),
+            None => return
+        };
+        // new elements follow the last one in its file
+        let source = sources.last().copied().unwrap_or_else(|| self.insert_source(section_name));
+        let mut next_position = ids.last().and_then(|id| self.document(source).position(*id)).map(|index| index + 1);
+        if let Some(section) = self.get_section_mut(section_name) {
+            let name_matching = section.name_matching;
+            for key_value in section.values.iter_mut().filter(|key_value| name_matching.matches(&key_value.key, array_key)) {
+                if let Some(index) = ids.iter().position(|id| *id == key_value.id) {
+                    if let Some(element) = elements.get(index) {
+                        key_value.value = element.clone();
+                    }
+                }
+            }
+            let surplus: Vec<(usize, usize)> = ids.iter().copied().zip(sources.iter().copied()).skip(elements.len()).collect();
+            section.values.retain(|key_value| !surplus.iter().any(|(id, _)| *id == key_value.id));
+            for (id, surplus_source) in surplus {
+                let document = self.document_mut(surplus_source);
+                if let Some(index) = document.position(id) {
+                    document.remove(index, false);
+                }
+            }
+        }
+        for element in elements.iter().skip(ids.len()) {
+            let id = self.next_id;
+            self.next_id += 1;
+            if let Some(section) = self.get_section_mut(section_name) {
+                section.push(KeyValuePair::new(array_key.to_string(), element.clone(), (0, 0), id, source));
+            }
+            let key_value_line = KeyValueLine::new(id, array_key, element, None);
+            match next_position {
+                Some(position) => self.document_mut(source).insert(position, LineKind::KeyValue(key_value_line)),
+                None => self.insert_key_value_line(section_name, source, key_value_line)
+            }
+            next_position = self.document(source).position(id).map(|index| index + 1);
+        }
+    }
+
+    // Inserts in a document the line of a new key/value pair
+    // after the last line of its section
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `section_name` - A string slice that holds the name of the Section
+    //  * `source` - The file of the new key/value pair, see KeyValuePair
+    //  * `key_value_line` - The tokens of the new line
+    fn insert_key_value_line(&mut self, section_name: &str, source: usize, key_value_line: KeyValueLine) {
+        // a new section tag is written as the section name has been written
+        let section_name = self.get_section(section_name).map(|section| section.name.clone()).unwrap_or_else(|| section_name.to_string());
+        let name_matching = self.name_matching;
+        let document = self.document_mut(source);
+        let position = match Self::section_end(document, &section_name, name_matching) {
+            Some(position) => position,
+            None => {
+                Self::append_section_tag_line(document, &section_name);
+                document.lines.len()
+            }
+        };
+        document.insert(position, LineKind::KeyValue(key_value_line));
+    }
+
+    // Returns the file where a new key/value pair of a section is inserted:
+    // the file of the last key/value pair of the section, the settings file
+    // if the section has no key/value pairs
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` - A string slice that holds the name of the Section
+    fn insert_source(&self, section_name: &str) -> usize {
+        self.get_section(section_name)
+            .and_then(|section| section.values.last())
+            .map(|key_value| key_value.source)
+            .unwrap_or(0)
+    }
+
+    // Returns the document of a source, see KeyValuePair
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `source` - 0 for the settings file, the included file position plus one
+    fn document(&self, source: usize) -> &Document {
+        match source {
+            0 => &self.document,
+            _ => &self.includes[source - 1].document
+        }
     }
 
+    // Returns the mutable document of a source, see KeyValuePair
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `source` - 0 for the settings file, the included file position plus one
+    fn document_mut(&mut self, source: usize) -> &mut Document {
+        match source {
+            0 => &mut self.document,
+            _ => &mut self.includes[source - 1].document
+        }
+    }
 
-    // This method format the error message string in base to the message index
-    // and parameters passed as a vector of string reference
-    // Returns the formatted error message
-    // in the message placeholders '{}'
-    //  * `self` -  An immutable reference to Setting struct
-    //  * `message_idx` -  Message index to format
-    //  * `params` -  A vector od String reference to format the message
-    fn format_message(&self, message_idx: usize, params: Vec<&String>) -> String {
-        let mut message = self.messages_table[message_idx].clone();
-        let mut i = 0usize;
-        while let Some(_) = message.find("{}"){
-            message = message.replacen("{}", &params[i].to_owned(), 1);
-            i = i + 1;
-            if i >= params.len() {
-                break;
+    // Removes from the document the lines of a removed section:
+    // all the tag lines of the section and the lines following them up to the next
+    // section tag and the key/value lines found before the first section tag
+    // in the settings file and in the included files, include directives are kept
+    //  * `self` -  A mutable reference to Setting struct
+    //  * `section` - The removed Section
+    //  * `comments` - If true the comment lines right above the removed lines are removed too
+    fn remove_section_lines(&mut self, section: &Section, comments: bool) {
+        let ids: Vec<usize> = section.values.iter().map(|key_value| key_value.id).collect();
+        let documents = std::iter::once(&mut self.document).chain(self.includes.iter_mut().map(|include| &mut include.document));
+        for document in documents {
+            let mut removed = vec![false; document.lines.len()];
+            let mut in_section = false;
+            for (index, line) in document.lines.iter().enumerate() {
+                if let Some(name) = line.section_name() {
+                    in_section = section.name_matching.matches(name, &section.name);
+                    if in_section && comments {
+                        document.mark_comments_above(index, &mut removed);
+                    }
+                } else if let Some(id) = line.key_value_id() {
+                    if !in_section && ids.contains(&id) {
+                        removed[index] = true;
+                        if comments {
+                            document.mark_comments_above(index, &mut removed);
+                        }
+                    }
+                }
+                if in_section && !line.is_directive() {
+                    removed[index] = true;
+                }
             }
+            document.remove_lines(&removed);
         }
-
-        message
     }
 
     // Returns if the Setting is already initialized or not
-    // The Settings is initialized if the settings file has beee
-    // successfuly loaded and so the path has been set
+    // The Settings is initialized if the settings file or source has beee
+    // successfuly loaded or saved with Settings::save_as
     //  * `self` -  An immutable reference to Setting struct
     fn is_initialize(&self) -> bool {
-        0 != self.path.len()
+        self.initialized
     }
 
     // Returns the setting file line type, see LineType enumeration
@@ -712,15 +2985,15 @@ impl Settings {
     //  * `line_text` -  A reference to the setting's file text line to anylize 
     //  * `line_cnt` -  A reference to text line counter
     //  * `settings_file` -  A string slice that holds the name of the setting file path 
-    fn line_type(&self, line_text: &String, line_cnt: &usize, settings_file: &str) -> LineType {
-        let mut trimmed_line = line_text.clone();
+    fn line_type(&self, line_text: &str, line_cnt: &usize, settings_file: &str) -> LineType {
+        let mut trimmed_line = line_text.to_string();
         if let Some(index) = trimmed_line.find(COMMENT_TAG) {
             trimmed_line.truncate(index)
         }
 
 
         let trimmed_line = trimmed_line.trim();
-        if 0 == trimmed_line.len() {
+        if trimmed_line.is_empty() {
             return LineType::EmptyLine;
         }
         let starts_with = trimmed_line.starts_with(START_SECTION_TAG);
@@ -728,7 +3001,7 @@ impl Settings {
 
         if starts_with && ends_with {
             let mut section_name = trimmed_line[1..trimmed_line.len() - 1].to_string();
-            if 0 == section_name.len() {
+            if section_name.is_empty() {
                 section_name = String::from(GLOBAL_SECTION);
             } 
             return LineType::SectionLine(section_name);
@@ -736,65 +3009,109 @@ impl Settings {
             let tag = END_SECTION_TAG.to_string();
             let line = format!("{}", line_cnt);
             let path = settings_file.to_string();
-            let error = self.format_message(MISSING_END_SECTION_TAG_MESSAGE_IDX, 
+            let message = self.format_message(MISSING_END_SECTION_TAG_MESSAGE_IDX, 
                 vec![&tag, &line, &path]);
-            return LineType::BadFormattedLine(error);
-        } else if !starts_with && ends_with {
+            return LineType::BadFormattedLine(SettingsError::MissingEndSectionTag { line: *line_cnt, path, message });
+        } else if !starts_with && ends_with && !trimmed_line.contains(ASSIGN_TAG) {
             let tag = START_SECTION_TAG.to_string();
             let line = format!("{}", line_cnt);
             let path = settings_file.to_string();
-            let error = self.format_message(MISSING_START_SECTION_TAG_MESSAGE_IDX,  
+            let message = self.format_message(MISSING_START_SECTION_TAG_MESSAGE_IDX,  
                 vec![&tag, &line, &path]);
-            return LineType::BadFormattedLine(error);
+            return LineType::BadFormattedLine(SettingsError::MissingStartSectionTag { line: *line_cnt, path, message });
         }
 
-        if let Some(assign_pos) = trimmed_line.find(ASSIGN_TAG) {
-            let (mut key, mut value) = trimmed_line.split_at(assign_pos);
-            key = key.trim();
-            let removed_assign = value.replace(ASSIGN_TAG, "");
-            value = removed_assign.trim();
-            if 0 == key.len() {
+        let content_end = line_text.find(COMMENT_TAG).unwrap_or(line_text.len());
+        if let Some(assign_pos) = line_text[..content_end].find(ASSIGN_TAG) {
+            let key = line_text[..assign_pos].trim();
+            if key.is_empty() {
                 let line = format!("{}", line_cnt);
                 let path = settings_file.to_string();
-                let error = self.format_message(MISSING_KEY_MESSAGE_IDX,
+                let message = self.format_message(MISSING_KEY_MESSAGE_IDX,
                     vec![&line, &path]);
-                return LineType::BadFormattedLine(error);
+                return LineType::BadFormattedLine(SettingsError::MissingKey { line: *line_cnt, path, message });
             }
-            return LineType::KeyAndValue(key.to_string(), value.to_string());
+            let after_assign = &line_text[assign_pos + ASSIGN_TAG.len()..];
+            let value_start = line_text.len() - after_assign.trim_start().len();
+            let value_text = &line_text[value_start..];
+            let (value, value_end) = if value_text.starts_with(document::QUOTES) {
+                // a quoted value can contain comment tags and spaces,
+                // only a comment can follow it
+                match document::decode_quoted(value_text) {
+                    Some((value, quoted_len)) if Self::is_comment_or_empty(&value_text[quoted_len..]) => {
+                        (value, value_start + quoted_len)
+                    },
+                    _ => {
+                        let line = format!("{}", line_cnt);
+                        let path = settings_file.to_string();
+                        let message = self.format_message(BAD_QUOTED_VALUE_MESSAGE_IDX,
+                            vec![&line, &path]);
+                        return LineType::BadFormattedLine(SettingsError::BadQuotedValue { line: *line_cnt, path, message });
+                    }
+                }
+            } else {
+                let content_end = value_text.find(COMMENT_TAG).unwrap_or(value_text.len());
+                let value = value_text[..content_end].trim_end();
+                (value.to_string(), value_start + value.len())
+            };
+            let key_value_line = KeyValueLine {
+                id: 0,
+                prefix: line_text[..value_start].to_string(),
+                raw_value: line_text[value_start..value_end].to_string(),
+                value,
+                suffix: line_text[value_end..].to_string(),
+                continuation: None
+            };
+            return LineType::KeyAndValue(key.to_string(), key_value_line);
         }
 
         let tag = ASSIGN_TAG.to_string();
         let line = format!("{}", line_cnt);
         let path = settings_file.to_string();
-        let error = self.format_message(MISSING_ASSIGN_TAG_MESSAGE_IDX, 
+        let message = self.format_message(MISSING_ASSIGN_TAG_MESSAGE_IDX, 
             vec![&tag, &line, &path]);
-        return LineType::BadFormattedLine(error);
+        LineType::BadFormattedLine(SettingsError::MissingAssignTag { line: *line_cnt, path, message })
+    }
+
+    // Returns true if the text after a quoted value contains only spaces and a comment
+    //  * `text` -  The text following the closing quote
+    fn is_comment_or_empty(text: &str) -> bool {
+        let text = text.trim_start();
+        text.is_empty() || text.starts_with(COMMENT_TAG)
     }
 
-    // Adds a key/value pair to a Section
+    // Adds a key/value pair read from a file to a Section, a key already
+    // read from another file (see include directives) is overridden
     //  * `self` -  A mutable reference to Setting struct
     //  * `section_name` - A string slice that holds the name of the Section
-    //  * `key` -  A string slice that holds the name of the inside the Section
-    //  * `line_cnt` -  A reference to text line counter where the key has been found
+    //  * `key_value` -  The key/value pair
     //  * `settings_file` -  A string slice that holds the name of the setting file path 
-    fn add_to_section(&mut self, section_name: &String, key: String, value: String, line_cnt: usize, settings_file: &str) -> StdResult<(), String> {
-        let mut iter = self.sections.iter_mut();
-        while let Some(section) = iter.next() {
-            if section.name == *section_name {
-                let kname = key.clone();
-                if let StdResult::Err(previous_line) = section.add(key, value, line_cnt) {
-                    let line = format!("{}", line_cnt);
-                    let previous_line = format!("{}", previous_line);
-                    let path = settings_file.to_string();               
-                    let error = self.format_message(DUPLICATED_KEY_MESSAGE_IDX, 
-                        vec![&kname, &line, &previous_line, &path]);
-                    return StdResult::Err(error);
-                }
+    fn add_to_section(&mut self, section_name: &str, key_value: KeyValuePair, settings_file: &str) -> StdResult<(), SettingsError> {
+        let line_cnt = key_value.line_cnt;
+        let array_element = self.array_keys && key_value.key.ends_with(ARRAY_TAG);
+        if let Some(section) = self.get_section_mut(section_name) {
+            if array_element {
+                section.push(key_value);
+                return StdResult::Ok(());
+            }
+            if let Some(previous) = section.values.iter_mut()
+                .find(|previous| previous.source != key_value.source && section.name_matching.matches(&previous.key, &key_value.key)) {
+                *previous = key_value;
                 return StdResult::Ok(());
             }
+            let kname = key_value.key.clone();
+            if let StdResult::Err(previous_line) = section.add(key_value) {
+                let line = format!("{}", line_cnt);
+                let previous = format!("{}", previous_line);
+                let path = settings_file.to_string();               
+                let message = self.format_message(DUPLICATED_KEY_MESSAGE_IDX, 
+                    vec![&kname, &line, &previous, &path]);
+                return StdResult::Err(SettingsError::DuplicatedKey { key: kname, line: line_cnt, previous_line, path, message });
+            }
+            return StdResult::Ok(());
         }
-        let mut section = Section::new(&section_name);
-        let _ = section.add(key, value, line_cnt);
+        let mut section = Section::new(section_name, 0, self.name_matching);
+        let _ = section.add(key_value);
         self.sections.push(section);
         StdResult::Ok(())
     }
@@ -804,13 +3121,7 @@ impl Settings {
     //  * `self` -  An immutable reference to Setting struct
     //  * `section_name` - A string slice that holds the name of the Section
     fn get_section(&self, section_name: &str) -> Option<&Section> {
-        let mut iter = self.sections.iter();
-        while let Some(section) = iter.next() {
-            if section.name == section_name {
-                return Some(section);
-            }
-        }
-        None
+        self.sections.iter().find(|section| self.name_matching.matches(&section.name, section_name))
     }
 
     // Returns a core::option::Option::Some() containing an mutable reference to Section
@@ -818,13 +3129,44 @@ impl Settings {
     //  * `self` -  A mutable reference to Setting struct
     //  * `section_name` - A string slice that holds the name of the Section
     fn get_section_mut(&mut self, section_name: &str) -> Option<&mut Section> {
-        let mut iter = self.sections.iter_mut();
-        while let Some(section) = iter.next() {
-            if section.name == section_name {
-                return Some(section);
-            }
-        }
-        None
+        let name_matching = self.name_matching;
+        self.sections.iter_mut().find(|section| name_matching.matches(&section.name, section_name))
+    }
+}
+
+/// implementation of FromStr trait for the Settings structure
+/// The settings are loaded from the string with english error messages,
+/// use Settings::load_from_reader to load a string with user language messages
+/// # Examples
+/// ```
+/// use rssettings::Settings;
+/// 
+/// fn main() {
+///     match "[LOG]\nenabled = true\n".parse::<Settings>() {
+///         Result::Ok(settings) => {
+///             assert_eq!(true, settings.get("LOG", "enabled", false).value);
+///         },
+///         Result::Err(error) => {
+///             eprintln!("{}", error);
+///         }
+///     }
+/// }
+/// ```
+impl FromStr for Settings {
+    type Err = SettingsError;
+
+    fn from_str(text: &str) -> StdResult<Self, Self::Err> {
+        let mut settings = Settings::new();
+        settings.load_from_reader(text.as_bytes(), STRING_SOURCE_NAME)?;
+        StdResult::Ok(settings)
+    }
+}
+
+// implementation of Default trait for the Settings structure
+// same as Settings::new
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -842,20 +3184,32 @@ impl Settings {
 /// ```
 impl Display for Settings {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Settings path: {}\n", self.path)?;
-        let mut iter = self.sections.iter();
-        while let Some(section) = iter.next() {
+        writeln!(f, "Settings path: {}", self.path)?;
+        for section in &self.sections {
             write!(f, "{}", section)?;
         }
-        write!(f, "====================================================================\n") 
+        writeln!(f, "====================================================================") 
     }
 }
 
 // implementation of Drop trait for the Settings structure
+// Settings is saved according to its drop policy, the save error
+// is passed to the drop error handler or its message, holding the path
+// of the file, is printed as a line on the standard error
 impl Drop for Settings {
     fn drop(&mut self) {
-        if let StdResult::Err(error) = self.save() {
-            eprint!("'{}': {:#?}", self.path, error);
+        let save = match self.drop_policy {
+            DropPolicy::Never => false,
+            DropPolicy::IfDirty => self.dirty,
+            DropPolicy::Always => true
+        };
+        if save {
+            if let StdResult::Err(error) = self.save() {
+                match self.drop_error_handler.as_mut() {
+                    Some(handler) => handler(error),
+                    None => eprintln!("{}", error)
+                }
+            }
         }
     }
 }
@@ -868,68 +3222,91 @@ mod tests {
     use std::thread::{self, Builder};
     use std::sync::{Arc, Mutex};
     use std::time::Duration;
+    use std::fs;
+
+    // Copies a test file in the temporary directory so that tests
+    // saving settings do not modify the files under test_files
+    // Returns the path of the copy
+    fn temp_copy(settings_file_path: &str, name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("rssettings_{}_{}.ini", std::process::id(), name));
+        fs::copy(settings_file_path, &path).unwrap();
+        path.to_str().unwrap().to_string()
+    }
 
     #[test]
     fn load_errors() {
         let mut settings_file_path ="goofy.ini";
         let mut settings = Settings::new();
-        assert_ne!(Result::Ok(()), settings.load(settings_file_path));
+        assert!(matches!(settings.load(settings_file_path), Err(SettingsError::OpeningFile { .. })));
         settings_file_path ="test_files";
-        assert_ne!(Result::Ok(()), settings.load(settings_file_path));
+        let error = settings.load(settings_file_path).unwrap_err();
+        assert!(matches!(error, SettingsError::ReadingFile { line: 1, .. }));
+        assert!(error.source().is_some());
     }
     #[test]
     fn missing_start_section_tag() {
         let settings_file_path ="test_files/missing_start_section_tag.ini";
         let mut settings = Settings::new();
-        let error = StdResult::Err(
-            format!("Missing start section tag '{}' at line '{}' of settings file: '{}'",
-             START_SECTION_TAG, 7, settings_file_path));
-        assert_eq!(error, settings.load(settings_file_path));
+        let error = format!("Missing start section tag '{}' at line '{}' of settings file: '{}'",
+             START_SECTION_TAG, 7, settings_file_path);
+        let result = settings.load(settings_file_path);
+        assert!(matches!(result, Err(SettingsError::MissingStartSectionTag { line: 7, .. })));
+        assert_eq!(error, result.unwrap_err().to_string());
     }
 
     #[test]
     fn missing_end_section_tag() {
         let settings_file_path ="test_files/missing_end_section_tag.ini";
         let mut settings = Settings::new();
-        let error = StdResult::Err(
-            format!("Missing end section tag '{}' at line '{}' of settings file: '{}'",
-             END_SECTION_TAG, 11, settings_file_path));
-        assert_eq!(error, settings.load(settings_file_path));
+        let error = format!("Missing end section tag '{}' at line '{}' of settings file: '{}'",
+             END_SECTION_TAG, 11, settings_file_path);
+        let result = settings.load(settings_file_path);
+        assert!(matches!(result, Err(SettingsError::MissingEndSectionTag { line: 11, .. })));
+        assert_eq!(error, result.unwrap_err().to_string());
     }
     #[test]
     fn missing_assign_tag() {
         let settings_file_path ="test_files/missing_assign_tag.ini";
         let mut settings = Settings::new();
-        let error = StdResult::Err(
-            format!("Missing assign tag '{}' at line '3' of settings file: '{}'", ASSIGN_TAG, settings_file_path));
-        assert_eq!(error, settings.load(settings_file_path));
+        let error = format!("Missing assign tag '{}' at line '3' of settings file: '{}'", ASSIGN_TAG, settings_file_path);
+        let result = settings.load(settings_file_path);
+        assert!(matches!(result, Err(SettingsError::MissingAssignTag { line: 3, .. })));
+        assert_eq!(error, result.unwrap_err().to_string());
     }
 
     #[test]
     fn missing_key() {
         let settings_file_path ="test_files/missing_key.ini";
         let mut settings = Settings::new();
-        let error = StdResult::Err(
-            format!("Missing key at line '5' of settings file: '{}'", settings_file_path)
-        );
-        assert_eq!(error, settings.load(settings_file_path));
+        let error = format!("Missing key at line '5' of settings file: '{}'", settings_file_path);
+        let result = settings.load(settings_file_path);
+        assert!(matches!(result, Err(SettingsError::MissingKey { line: 5, .. })));
+        assert_eq!(error, result.unwrap_err().to_string());
     }
 
     #[test]
     fn duplicated_key() {
         let settings_file_path ="test_files/duplicated_key.ini";
         let mut settings = Settings::new();
-        let error = StdResult::Err(
-            format!("Duplicated key 'key1' at line '5' previously defined at line '2' of settings file: '{}'", settings_file_path)
-            );
-        assert_eq!(error, settings.load(settings_file_path));
+        let error = format!("Duplicated key 'key1' at line '5' previously defined at line '2' of settings file: '{}'", settings_file_path);
+        let result = settings.load(settings_file_path);
+        match result {
+            Err(SettingsError::DuplicatedKey { ref key, line, previous_line, ref path, .. }) => {
+                assert_eq!("key1", key);
+                assert_eq!(5, line);
+                assert_eq!(2, previous_line);
+                assert_eq!(settings_file_path, path);
+            },
+            _ => panic!("DuplicatedKey error expected")
+        }
+        assert_eq!(error, result.unwrap_err().to_string());
     }
 
     #[test]
     fn key_value_to_global() {
         let settings_file_path ="test_files/key_value_to_global.ini";
         let mut settings = Settings::new();
-        assert_eq!(Result::Ok(()), settings.load(settings_file_path));
+        assert!(settings.load(settings_file_path).is_ok());
 
         let settings_dump = 
 "Settings path: test_files/key_value_to_global.ini
@@ -952,131 +3329,137 @@ key: key1, value: def
     fn set_get_errors() {
         let settings_file_path ="test_files/set_get_errors.ini";
         let mut settings = Settings::new();
-        assert_eq!(Result::Ok(()), settings.load(settings_file_path));
-        assert_eq!("Section 'GENERLA' not found".to_string(), settings.get("GENERLA", "enabled", false).error);
-        assert_eq!("Section 'GENERAL' key 'enable' not found".to_string(), settings.get("GENERAL", "enable", false).error);
+        assert!(settings.load(settings_file_path).is_ok());
+        let result = settings.get("GENERLA", "enabled", false);
+        assert!(matches!(result.error, Some(SettingsError::SectionNotFound { .. })));
+        assert_eq!("Section 'GENERLA' not found".to_string(), result.error.unwrap().to_string());
+        let result = settings.get("GENERAL", "enable", false);
+        assert!(matches!(result.error, Some(SettingsError::KeyNotFound { .. })));
+        assert_eq!("Section 'GENERAL' key 'enable' not found".to_string(), result.error.unwrap().to_string());
         let error = "a123".parse::<i32>().err().unwrap();
         let mut error_as_string = format!("Section 'GENERAL' key 'integer_value', Parsing error: '{:#?}'", error);
-        assert_eq!(error_as_string, settings.get("GENERAL", "integer_value", 10).error);
+        let result = settings.get("GENERAL", "integer_value", 10);
+        assert!(matches!(result.error, Some(SettingsError::Parsing { .. })));
+        assert_eq!(error_as_string, result.error.unwrap().to_string());
 
         let error = "123a.35".parse::<f32>().err().unwrap();
         error_as_string = format!("Section 'GENERAL' key 'float_value', Parsing error: '{:#?}'", error);
-        assert_eq!(error_as_string, settings.get("GENERAL", "float_value", -1.0f32).error);
+        assert_eq!(error_as_string, settings.get("GENERAL", "float_value", -1.0f32).error.unwrap().to_string());
 
         let error = "ciao".parse::<bool>().err().unwrap();
         error_as_string = format!("Section 'GENERAL' key 'enabled', Parsing error: '{:#?}'", error);
-        assert_eq!(error_as_string, settings.get("GENERAL", "enabled", true).error);
+        assert_eq!(error_as_string, settings.get("GENERAL", "enabled", true).error.unwrap().to_string());
 
         let mut error = "Section 'GLOBAL' not found".to_string();
-        assert_eq!(StdResult::Err(error), settings.set(GLOBAL_SECTION, "enabled", true));
+        assert_eq!(error, settings.set(GLOBAL_SECTION, "enabled", true).unwrap_err().to_string());
         error = "Section 'GENERAL' key 'enable' not found".to_string();
-        assert_eq!(StdResult::Err(error), settings.set("GENERAL", "enable", false));
+        assert_eq!(error, settings.set("GENERAL", "enable", false).unwrap_err().to_string());
     } 
 
     #[test]
     fn no_section_name() {
         let settings_file_path ="test_files/no_section_name.ini";
         let mut settings = Settings::new();
-        assert_eq!(Result::Ok(()), settings.load(settings_file_path));
+        assert!(settings.load(settings_file_path).is_ok());
         let result = settings.get(GLOBAL_SECTION, "title", "???".to_string());
-        assert!((result.error.len() == 0 && result.value == "Test empty section name".to_string()));
+        assert!(result.error.is_none() && result.value == "Test empty section name");
     }
 
     #[test]
     fn get_set_ok() {
         let settings_file_path ="test_files/settings.ini";
         let mut settings = Settings::new();
-        assert_eq!(Result::Ok(()), settings.load(settings_file_path));
+        assert!(settings.load(settings_file_path).is_ok());
 
         let mut result = settings.get(GLOBAL_SECTION, "bool_value", false);
-        assert!(result.error.len() == 0 && true == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "bool_value", false));
+        assert!(result.error.is_none() && result.value);
+        assert!(settings.set(GLOBAL_SECTION, "bool_value", false).is_ok());
         result = settings.get(GLOBAL_SECTION, "bool_value", true);
-        assert!(result.error.len() == 0 && false == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "bool_value", true));
+        assert!(result.error.is_none() && !result.value);
+        assert!(settings.set(GLOBAL_SECTION, "bool_value", true).is_ok());
         result = settings.get(GLOBAL_SECTION, "bool_value", false);
-        assert!(result.error.len() == 0 && true == result.value);
+        assert!(result.error.is_none() && result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "i32_value", -1000i32);
-        assert!(result.error.len() == 0 && -100i32 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "i32_value", -1000i32));
+        assert!(result.error.is_none() && -100i32 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "i32_value", -1000i32).is_ok());
         result = settings.get(GLOBAL_SECTION, "i32_value", -100i32);
-        assert!(result.error.len() == 0 && -1000i32 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "i32_value", -100i32));
+        assert!(result.error.is_none() && -1000i32 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "i32_value", -100i32).is_ok());
         result = settings.get(GLOBAL_SECTION, "i32_value", -1000i32);
-        assert!(result.error.len() == 0 && -100i32 == result.value);
+        assert!(result.error.is_none() && -100i32 == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "u32_value", 1000u32);
-        assert!(result.error.len() == 0 && 100u32 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "u32_value", 1000u32));
+        assert!(result.error.is_none() && 100u32 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "u32_value", 1000u32).is_ok());
         result = settings.get(GLOBAL_SECTION, "u32_value", 100u32);
-        assert!(result.error.len() == 0 && 1000u32 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "u32_value", 100u32));
+        assert!(result.error.is_none() && 1000u32 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "u32_value", 100u32).is_ok());
         result = settings.get(GLOBAL_SECTION, "u32_value", 1000u32);
-        assert!(result.error.len() == 0 && 100u32 == result.value);
+        assert!(result.error.is_none() && 100u32 == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "i64_value", -2000i64);
-        assert!(result.error.len() == 0 && -200i64 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "i64_value", -2000i64));
+        assert!(result.error.is_none() && -200i64 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "i64_value", -2000i64).is_ok());
         result = settings.get(GLOBAL_SECTION, "i64_value", -200i64);
-        assert!(result.error.len() == 0 && -2000i64 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "i64_value", -200i64));
+        assert!(result.error.is_none() && -2000i64 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "i64_value", -200i64).is_ok());
         result = settings.get(GLOBAL_SECTION, "i64_value", -2000i64);
-        assert!(result.error.len() == 0 && -200i64 == result.value);
+        assert!(result.error.is_none() && -200i64 == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "u64_value", 2000u64);
-        assert!(result.error.len() == 0 && 200u64 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "u64_value", 2000u64));
+        assert!(result.error.is_none() && 200u64 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "u64_value", 2000u64).is_ok());
         result = settings.get(GLOBAL_SECTION, "u64_value", 200u64);
-        assert!(result.error.len() == 0 && 2000u64 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "u64_value", 200u64));
+        assert!(result.error.is_none() && 2000u64 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "u64_value", 200u64).is_ok());
         result = settings.get(GLOBAL_SECTION, "u64_value", 2000u64);
-        assert!(result.error.len() == 0 && 200u64 == result.value);
+        assert!(result.error.is_none() && 200u64 == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "isize_value", -3000isize);
-        assert!(result.error.len() == 0 && -300isize == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "isize_value", -3000isize));
+        assert!(result.error.is_none() && -300isize == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "isize_value", -3000isize).is_ok());
         result = settings.get(GLOBAL_SECTION, "isize_value", -300isize);
-        assert!(result.error.len() == 0 && -3000isize == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "isize_value", -300isize));
+        assert!(result.error.is_none() && -3000isize == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "isize_value", -300isize).is_ok());
         result = settings.get(GLOBAL_SECTION, "isize_value", -3000isize);
-        assert!(result.error.len() == 0 && -300isize == result.value);
+        assert!(result.error.is_none() && -300isize == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "usize_value", 3000usize);
-        assert!(result.error.len() == 0 && 300usize == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "usize_value", 3000usize));
+        assert!(result.error.is_none() && 300usize == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "usize_value", 3000usize).is_ok());
         result = settings.get(GLOBAL_SECTION, "usize_value", 300usize);
-        assert!(result.error.len() == 0 && 3000usize == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "usize_value", 300usize));
+        assert!(result.error.is_none() && 3000usize == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "usize_value", 300usize).is_ok());
         result = settings.get(GLOBAL_SECTION, "usize_value", 3000usize);
-        assert!(result.error.len() == 0 && 300usize == result.value);
+        assert!(result.error.is_none() && 300usize == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "f32_value", -32.400f32);
-        assert!(result.error.len() == 0 && -400.32f32 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "f32_value", -32.400f32));
+        assert!(result.error.is_none() && -400.32f32 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "f32_value", -32.400f32).is_ok());
         result = settings.get(GLOBAL_SECTION, "f32_value", -400.32f32);
-        assert!(result.error.len() == 0 && -32.400f32 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "f32_value", -400.32f32));
+        assert!(result.error.is_none() && -32.400f32 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "f32_value", -400.32f32).is_ok());
         result = settings.get(GLOBAL_SECTION, "f32_value", -32.400f32);
-        assert!(result.error.len() == 0 && -400.32f32 == result.value);
+        assert!(result.error.is_none() && -400.32f32 == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "f64_value", 64.400f64);
-        assert!(result.error.len() == 0 && 400.64f64 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "f64_value", 64.400f64));
+        assert!(result.error.is_none() && 400.64f64 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "f64_value", 64.400f64).is_ok());
         result = settings.get(GLOBAL_SECTION, "f64_value", 400.64f64);
-        assert!(result.error.len() == 0 && 64.400f64 == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "f64_value", 400.64f64));
+        assert!(result.error.is_none() && 64.400f64 == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "f64_value", 400.64f64).is_ok());
         result = settings.get(GLOBAL_SECTION, "f64_value", 64.400f64);
-        assert!(result.error.len() == 0 && 400.64f64 == result.value);
+        assert!(result.error.is_none() && 400.64f64 == result.value);
 
         let mut result = settings.get(GLOBAL_SECTION, "string_value", "boh!!!".to_string());
-        assert!(result.error.len() == 0 && "The quick brown fox jump over the lazy dog" == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "string_value", "boh!!!".to_string()));
+        assert!(result.error.is_none() && "The quick brown fox jump over the lazy dog" == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "string_value", "boh!!!".to_string()).is_ok());
         result = settings.get(GLOBAL_SECTION, "string_value", "???".to_string());
-        assert!(result.error.len() == 0 && "boh!!!" == result.value);
-        assert!(Result::Ok(()) == settings.set(GLOBAL_SECTION, "string_value", "The quick brown fox jump over the lazy dog".to_string()));
+        assert!(result.error.is_none() && "boh!!!" == result.value);
+        assert!(settings.set(GLOBAL_SECTION, "string_value", "The quick brown fox jump over the lazy dog".to_string()).is_ok());
         result = settings.get(GLOBAL_SECTION, "string_value", "boh!!!".to_string());
-        assert!(result.error.len() == 0 && "The quick brown fox jump over the lazy dog" == result.value);
+        assert!(result.error.is_none() && "The quick brown fox jump over the lazy dog" == result.value);
         
         let mut original_settings = Settings::new();
         let _ = original_settings.load("test_files/original_settings.ini");
@@ -1084,15 +3467,1176 @@ key: key1, value: def
         assert!(settings.sections == original_settings.sections);
     }
 
+    #[test]
+    fn insert_sections_and_keys() {
+        let settings_file_path = temp_copy("test_files/set_get_errors.ini", "insert_sections_and_keys");
+        {
+            let mut settings = Settings::new();
+            assert!(settings.load(&settings_file_path).is_ok());
+            assert!(settings.set_or_insert("GENERAL", "enabled", true).is_ok());
+            assert!(settings.set_or_insert("GENERAL", "new_value", 10).is_ok());
+            assert!(settings.set_or_insert("NEW_SECTION", "key1", "abc").is_ok());
+            assert!(settings.add_section("EMPTY_SECTION").unwrap());
+            assert!(!settings.add_section("LOG").unwrap());
+            assert!(matches!(settings.set_or_insert("LOG", "bad = key", 1), Err(SettingsError::InvalidName { .. })));
+            assert!(matches!(settings.add_section("BAD]"), Err(SettingsError::InvalidName { .. })));
+            assert!(settings.save().is_ok());
+            assert!(settings.set_or_insert("LOG", "new_value", 20).is_ok());
+            assert!(settings.set_or_insert("NEW_SECTION", "key2", "def").is_ok());
+            assert!(settings.set_or_insert("EMPTY_SECTION", "key1", 1.5).is_ok());
+            assert!(settings.save().is_ok());
+        }
+        let expected = "#######################################################################
+# This is an example of a settings file
+# used to test rssettings crate
+#######################################################################
+[GENERAL]
+enabled = true
+integer_value = 1000a
+float_value = 123a.456
+string_value = the quick brown fox jump over the lazy dog
+new_value = 10
+
+[LOG]
+enabled = true
+log_level = 5 # 1 = fatal logs only, 2 = fatal & critical, 3 = fatal, critical & warning, 4 = fatal, critical, warning & debug, 4 = all logs 
+new_value = 20
+
+[NEW_SECTION]
+key1 = abc
+key2 = def
+
+[EMPTY_SECTION]
+key1 = 1.5
+";
+        assert_eq!(expected, fs::read_to_string(&settings_file_path).unwrap());
+        let mut settings = Settings::new();
+        assert!(settings.load(&settings_file_path).is_ok());
+        assert_eq!(20, settings.get("LOG", "new_value", 0).value);
+        assert_eq!("def", settings.get("NEW_SECTION", "key2", String::new()).value);
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn remove_sections_and_keys() {
+        let settings_file_path = temp_copy("test_files/remove.ini", "remove_sections_and_keys");
+        {
+            let mut settings = Settings::new();
+            assert!(settings.load(&settings_file_path).is_ok());
+            assert!(settings.remove_key("GENERAL", "enabled", true).is_ok());
+            assert!(settings.remove_key("GENERAL", "integer_value", false).is_ok());
+            assert!(matches!(settings.remove_key("GENERAL", "enabled", true), Err(SettingsError::KeyNotFound { .. })));
+            assert!(settings.remove_section("LOG", true).is_ok());
+            assert!(matches!(settings.remove_section("LOG", true), Err(SettingsError::SectionNotFound { .. })));
+            assert!(settings.save().is_ok());
+            assert!(settings.set("LAST", "key", "new value").is_ok());
+            assert!(settings.set(GLOBAL_SECTION, "global_key", 2).is_ok());
+            assert!(settings.save().is_ok());
+        }
+        let expected = "# global comment
+global_key = 2
+
+# general section
+[GENERAL]
+# integer comment
+
+[LAST]
+key = new value
+";
+        assert_eq!(expected, fs::read_to_string(&settings_file_path).unwrap());
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn lossless_save() {
+        let settings_file_path = std::env::temp_dir().join(format!("rssettings_{}_lossless_save.ini", std::process::id()));
+        let content = "# header comment\r\n\r\n[GLOBAL]\r\n  key1=value1\r\nkey2   =   value 2   # inline comment\r\n\tkey3 =\r\n[]\r\nkey4 = 4";
+        fs::write(&settings_file_path, content).unwrap();
+        {
+            let mut settings = Settings::new();
+            assert!(settings.load(&settings_file_path).is_ok());
+            assert_eq!("value 2", settings.get(GLOBAL_SECTION, "key2", String::new()).value);
+            assert!(settings.save().is_ok());
+            assert_eq!(content, fs::read_to_string(&settings_file_path).unwrap());
+
+            assert!(settings.set(GLOBAL_SECTION, "key2", "changed").is_ok());
+            assert!(settings.set(GLOBAL_SECTION, "key3", "x").is_ok());
+            assert!(settings.set_or_insert(GLOBAL_SECTION, "key5", 5).is_ok());
+            assert!(settings.save().is_ok());
+        }
+        let expected = "# header comment\r\n\r\n[GLOBAL]\r\n  key1=value1\r\nkey2   =   changed   # inline comment\r\n\tkey3 = x\r\n[]\r\nkey4 = 4\r\nkey5 = 5";
+        assert_eq!(expected, fs::read_to_string(&settings_file_path).unwrap());
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn load_from_reader_and_write_to() {
+        let content = "# comment\n[LOG]\nenabled = true\nlevel = 3 # verbosity\n";
+        let mut settings: Settings = content.parse().unwrap();
+        assert!(settings.load_from_reader(content.as_bytes(), "other").is_err());
+        assert_eq!(3, settings.get("LOG", "level", 0).value);
+        assert!(settings.save().is_ok());
+
+        assert!(settings.set("LOG", "level", 5).is_ok());
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!("# comment\n[LOG]\nenabled = true\nlevel = 5 # verbosity\n", String::from_utf8(buffer).unwrap());
+
+        let error = "[LOG\n".parse::<Settings>().err().unwrap();
+        assert!(matches!(error, SettingsError::MissingEndSectionTag { line: 1, .. }));
+        assert!(error.to_string().ends_with(&format!("'{}'", STRING_SOURCE_NAME)));
+
+        let mut settings = Settings::new();
+        assert!(settings.load_from_reader("[LOG]\nenabled = true\n".as_bytes(), "embedded").is_ok());
+        let settings_file_path = std::env::temp_dir().join(format!("rssettings_{}_save_as.ini", std::process::id()));
+        assert!(settings.save_as(&settings_file_path).is_ok());
+        assert!(settings.set("LOG", "enabled", false).is_ok());
+        assert!(settings.save().is_ok());
+        assert_eq!("[LOG]\nenabled = false\n", fs::read_to_string(&settings_file_path).unwrap());
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn atomic_save() {
+        let settings_file_path = temp_copy("test_files/settings.ini", "atomic_save");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&settings_file_path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let mut settings = Settings::new();
+        assert!(settings.load(&settings_file_path).is_ok());
+        assert!(settings.set(GLOBAL_SECTION, "bool_value", false).is_ok());
+        assert!(settings.save().is_ok());
+        assert!(fs::read_to_string(&settings_file_path).unwrap().contains("bool_value = false\n"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(0o640, fs::metadata(&settings_file_path).unwrap().permissions().mode() & 0o777);
+        }
+        let temp_files = fs::read_dir(std::env::temp_dir()).unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&format!(".rssettings_{}_atomic_save", std::process::id())))
+            .count();
+        assert_eq!(0, temp_files);
+
+        // threads saving the same file do not share their temporary files
+        let threads: Vec<_> = (0..4).map(|thread| {
+            let settings_file_path = settings_file_path.clone();
+            std::thread::spawn(move || {
+                let mut settings = Settings::new().with_drop_policy(DropPolicy::Never).with_conflict_policy(ConflictPolicy::Overwrite);
+                assert!(settings.load(&settings_file_path).is_ok());
+                for value in 0..20 {
+                    assert!(settings.set(GLOBAL_SECTION, "i32_value", thread * 100 + value).is_ok());
+                    assert!(settings.save().is_ok());
+                }
+            })
+        }).collect();
+        for thread in threads {
+            assert!(thread.join().is_ok());
+        }
+        let mut saved = Settings::new().with_drop_policy(DropPolicy::Never);
+        assert!(saved.load(&settings_file_path).is_ok());
+        assert_eq!(19, saved.get(GLOBAL_SECTION, "i32_value", 0).value % 100);
+
+        let missing_directory = std::env::temp_dir().join(format!("rssettings_{}_missing", std::process::id())).join("settings.ini");
+        let error = settings.save_as(&missing_directory).unwrap_err();
+        assert!(matches!(error, SettingsError::WritingFile { .. }));
+        assert!(error.source().is_some());
+        drop(settings);
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn drop_policy_and_dirty_flag() {
+        let settings_file_path = temp_copy("test_files/settings.ini", "drop_policy");
+        let content = fs::read_to_string(&settings_file_path).unwrap();
+        {
+            let mut settings = Settings::new().with_drop_policy(DropPolicy::Never);
+            assert!(settings.load(&settings_file_path).is_ok());
+            assert!(!settings.is_dirty());
+            assert!(settings.set(GLOBAL_SECTION, "bool_value", false).is_ok());
+            assert!(settings.is_dirty());
+        }
+        assert_eq!(content, fs::read_to_string(&settings_file_path).unwrap());
+        {
+            let mut settings = Settings::new();
+            assert_eq!(DropPolicy::IfDirty, settings.drop_policy());
+            assert!(settings.load(&settings_file_path).is_ok());
+            fs::remove_file(&settings_file_path).unwrap();
+        }
+        assert!(fs::metadata(&settings_file_path).is_err());
+        fs::write(&settings_file_path, &content).unwrap();
+        {
+            let mut settings = Settings::new();
+            assert!(settings.load(&settings_file_path).is_ok());
+            assert!(settings.remove_key(GLOBAL_SECTION, "bool_value", false).is_ok());
+            assert!(settings.is_dirty());
+            assert!(settings.save().is_ok());
+            assert!(!settings.is_dirty());
+            assert!(settings.set_or_insert(GLOBAL_SECTION, "bool_value", true).is_ok());
+        }
+        assert!(fs::read_to_string(&settings_file_path).unwrap().contains("bool_value = true"));
+
+        let drop_error: Arc<Mutex<Option<SettingsError>>> = Arc::new(Mutex::new(None));
+        let handler_error = Arc::clone(&drop_error);
+        {
+            let mut settings = Settings::new().with_drop_policy(DropPolicy::Always)
+                .with_drop_error_handler(move |error| {
+                    *handler_error.lock().unwrap() = Some(error);
+                });
+            assert!(settings.load(&settings_file_path).is_ok());
+            fs::remove_file(&settings_file_path).unwrap();
+            fs::create_dir(&settings_file_path).unwrap();
+        }
+        assert!(matches!(*drop_error.lock().unwrap(), Some(SettingsError::WritingFile { .. })));
+        fs::remove_dir(&settings_file_path).unwrap();
+    }
+
+    #[test]
+    fn reload_and_conflicts() {
+        let settings_file_path = temp_copy("test_files/settings.ini", "conflicts");
+        let content = fs::read_to_string(&settings_file_path).unwrap();
+        let external_content = content.replace("i32_value = -100", "i32_value = -101");
+        let mut settings = Settings::new();
+        assert_eq!(ConflictPolicy::Fail, settings.conflict_policy());
+        assert!(settings.load(&settings_file_path).is_ok());
+        assert!(settings.set(GLOBAL_SECTION, "bool_value", false).is_ok());
+        assert!(settings.reload().is_ok());
+        assert!(!settings.is_dirty());
+        assert!(settings.get(GLOBAL_SECTION, "bool_value", false).value);
+
+        assert!(settings.set(GLOBAL_SECTION, "bool_value", false).is_ok());
+        fs::write(&settings_file_path, &external_content).unwrap();
+        assert!(matches!(settings.save(), Err(SettingsError::Conflict { .. })));
+        assert_eq!(external_content, fs::read_to_string(&settings_file_path).unwrap());
+        assert!(settings.reload().is_ok());
+        assert_eq!(-101, settings.get(GLOBAL_SECTION, "i32_value", 0).value);
+        drop(settings);
+
+        let mut settings = Settings::new().with_conflict_policy(ConflictPolicy::Merge);
+        assert!(settings.load(&settings_file_path).is_ok());
+        assert!(settings.set(GLOBAL_SECTION, "bool_value", false).is_ok());
+        assert!(settings.set_or_insert("MERGE", "key", "value").is_ok());
+        assert!(settings.remove_key(GLOBAL_SECTION, "u32_value", false).is_ok());
+        assert!(settings.remove_key(GLOBAL_SECTION, "u64_value", false).is_ok());
+        // the other side removed the same key
+        fs::write(&settings_file_path, content.replace("u64_value = 200\n", "")).unwrap();
+        assert!(settings.save().is_ok());
+        assert_eq!(-100, settings.get(GLOBAL_SECTION, "i32_value", 0).value);
+        let merged_content = fs::read_to_string(&settings_file_path).unwrap();
+        assert!(merged_content.contains("i32_value = -100\n"));
+        assert!(merged_content.contains("bool_value = false\n"));
+        assert!(merged_content.contains("[MERGE]\nkey = value"));
+        assert!(!merged_content.contains("u32_value"));
+        assert!(!merged_content.contains("u64_value"));
+
+        fs::write(&settings_file_path, "[BROKEN\n").unwrap();
+        assert!(settings.reload().is_err());
+        assert!(settings.key_exists("MERGE", "key"));
+        drop(settings.with_drop_policy(DropPolicy::Never));
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn watch_settings_file() {
+        let settings_file_path = temp_copy("test_files/settings.ini", "watcher");
+        let content = fs::read_to_string(&settings_file_path).unwrap();
+        let mut settings = Settings::new().with_drop_policy(DropPolicy::Never);
+        assert!(settings.load(&settings_file_path).is_ok());
+        let settings = Arc::new(Mutex::new(settings));
+        let received: Arc<Mutex<Vec<SettingChange>>> = Arc::new(Mutex::new(vec![]));
+        let callback_received = Arc::clone(&received);
+        let errors = Arc::new(Mutex::new(0));
+        let callback_errors = Arc::clone(&errors);
+        let mut watcher = SettingsWatcher::new(Arc::clone(&settings))
+            .on_change(move |changes| callback_received.lock().unwrap().extend_from_slice(changes))
+            .on_error(move |_| *callback_errors.lock().unwrap() += 1);
+        assert!(watcher.check().is_empty());
+
+        fs::write(&settings_file_path, content.replace("i32_value = -100", "i32_value = -1").replace("u32_value = 100\n", "")).unwrap();
+        let changes = watcher.check();
+        assert_eq!(2, changes.len());
+        assert_eq!(SettingChange { section: GLOBAL_SECTION.to_string(), key: "i32_value".to_string(),
+            old_value: Some("-100".to_string()), new_value: Some("-1".to_string()) }, changes[0]);
+        assert_eq!(None, changes[1].new_value);
+        assert_eq!(changes, *received.lock().unwrap());
+        assert_eq!(-1, settings.lock().unwrap().get(GLOBAL_SECTION, "i32_value", 0).value);
+
+        fs::write(&settings_file_path, "[BROKEN\n").unwrap();
+        assert!(watcher.check().is_empty());
+        assert!(watcher.check().is_empty());
+        assert_eq!(1, *errors.lock().unwrap());
+        assert_eq!(-1, settings.lock().unwrap().get(GLOBAL_SECTION, "i32_value", 0).value);
+
+        // unsaved changes survive the reload
+        assert!(settings.lock().unwrap().set(GLOBAL_SECTION, "bool_value", false).is_ok());
+        fs::write(&settings_file_path, content.replace("i32_value = -100", "i32_value = -2")).unwrap();
+        let changes = watcher.check();
+        assert_eq!(2, changes.len());
+        assert_eq!(Some("-2".to_string()), changes[0].new_value);
+        assert_eq!(Some("100".to_string()), changes[1].new_value);
+        assert!(!settings.lock().unwrap().get(GLOBAL_SECTION, "bool_value", true).value);
+        assert!(settings.lock().unwrap().is_dirty());
+        assert!(!settings.lock().unwrap().is_modified_on_disk());
+
+        let handle = watcher.with_interval(Duration::from_millis(10)).start();
+        fs::write(&settings_file_path, &content).unwrap();
+        for _ in 0..300 {
+            if settings.lock().unwrap().get(GLOBAL_SECTION, "i32_value", 0).value == -100 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        handle.stop();
+        assert_eq!(-100, settings.lock().unwrap().get(GLOBAL_SECTION, "i32_value", 0).value);
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn watch_array_keys() {
+        let settings_file_path = std::env::temp_dir().join(format!("rssettings_{}_watcher_array.ini", std::process::id()));
+        fs::write(&settings_file_path, "[SERVERS]\nhost[] = a\nhost[] = b\nhost[] = c\n").unwrap();
+        let mut settings = Settings::new().with_array_keys(true).with_drop_policy(DropPolicy::Never);
+        assert!(settings.load(&settings_file_path).is_ok());
+        let mut watcher = SettingsWatcher::new(Arc::new(Mutex::new(settings)));
+        fs::write(&settings_file_path, "[SERVERS]\nhost[] = a\nhost[] = d\n").unwrap();
+        let changes = watcher.check();
+        assert_eq!(vec![
+            SettingChange { section: "SERVERS".to_string(), key: "host[]".to_string(),
+                old_value: Some("b".to_string()), new_value: Some("d".to_string()) },
+            SettingChange { section: "SERVERS".to_string(), key: "host[]".to_string(),
+                old_value: Some("c".to_string()), new_value: None }], changes);
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_deserialize_and_serialize() {
+        use serde::{Deserialize, Serialize};
+        use std::collections::HashMap;
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        enum Level {
+            Debug,
+            Error
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        struct Log {
+            enabled: bool,
+            level: Level,
+            file: Option<String>,
+            #[serde(default)]
+            max_size: u32,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        #[allow(non_snake_case)]
+        struct AppConfig {
+            GLOBAL: HashMap<String, String>,
+            LOG: Log,
+        }
+
+        let mut settings = Settings::new();
+        assert!(settings.load("test_files/settings.ini").is_ok());
+        let error = settings.deserialize::<AppConfig>().unwrap_err();
+        assert!(matches!(error, SettingsError::Serde { .. }));
+        assert_eq!("Serde error: 'missing field `LOG`'", error.to_string());
+
+        #[derive(Deserialize)]
+        struct Values {
+            i32_value: i32,
+            f64_value: f64,
+            string_value: String,
+        }
+        #[derive(Deserialize)]
+        #[allow(non_snake_case)]
+        struct Global {
+            GLOBAL: Values,
+        }
+        let global = settings.deserialize::<Global>().unwrap();
+        assert_eq!(-100, global.GLOBAL.i32_value);
+        assert_eq!(400.64, global.GLOBAL.f64_value);
+        assert_eq!("The quick brown fox jump over the lazy dog", global.GLOBAL.string_value);
+
+        let mut settings: Settings = "[GLOBAL]\nname = rssettings\n[LOG]\nenabled = yes\nlevel = Debug\n".parse().unwrap();
+        let error = settings.deserialize::<AppConfig>().unwrap_err();
+        assert!(matches!(error, SettingsError::Parsing { ref key, .. } if key == "enabled"));
+        assert!(settings.set("LOG", "enabled", true).is_ok());
+        let mut config = settings.deserialize::<AppConfig>().unwrap();
+        assert_eq!(Log { enabled: true, level: Level::Debug, file: None, max_size: 0 }, config.LOG);
+        assert_eq!("rssettings", config.GLOBAL["name"]);
+
+        config.LOG.level = Level::Error;
+        config.LOG.file = Some("app.log".to_string());
+        assert!(settings.serialize(&config).is_ok());
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!("[GLOBAL]\nname = rssettings\n[LOG]\nenabled = true\nlevel = Error\nfile = app.log\nmax_size = 0\n",
+            String::from_utf8(buffer).unwrap());
+        assert_eq!(config, settings.deserialize::<AppConfig>().unwrap());
+        assert!(matches!(settings.serialize(&42), Err(SettingsError::Serde { .. })));
+
+        // the elements of an array are a sequence
+        #[derive(Deserialize)]
+        struct Servers {
+            host: Vec<String>,
+            port: Vec<u16>,
+            name: String,
+        }
+        #[derive(Deserialize)]
+        #[allow(non_snake_case)]
+        struct Cluster {
+            SERVERS: Servers,
+        }
+        let text = "[SERVERS]\nhost[] = a\nport[] = 80\nhost[] = b\nname = main\nport[] = 443\n";
+        let mut settings = Settings::new().with_array_keys(true);
+        assert!(settings.load_from_reader(text.as_bytes(), "servers.ini").is_ok());
+        let cluster = settings.deserialize::<Cluster>().unwrap();
+        assert_eq!(vec!["a", "b"], cluster.SERVERS.host);
+        assert_eq!(vec![80, 443], cluster.SERVERS.port);
+        assert_eq!("main", cluster.SERVERS.name);
+        let mut settings = Settings::new().with_array_keys(true);
+        assert!(settings.load_from_reader(text.replace("443", "https").as_bytes(), "servers.ini").is_ok());
+        let error = settings.deserialize::<Cluster>().err().unwrap();
+        assert!(matches!(error, SettingsError::Parsing { ref key, line: 6, .. } if key == "port"));
+    }
+
+    #[test]
+    fn quoted_values() {
+        let content = concat!(
+            "[QUOTED]\n",
+            "password = \"abc#123\" # inline comment\n",
+            "padded = '  x  '\n",
+            "escapes = \"tab\\tquote\\\"back\\\\slash\\u00e8\\uD83D\\uDE00\"\n",
+            "url = http://host/path?a=1&b=2\n",
+            "base64 = YWJj==\n",
+            "bracket = x]\n");
+        let mut settings: Settings = content.parse().unwrap();
+        assert_eq!("abc#123", settings.get("QUOTED", "password", String::new()).value);
+        assert_eq!("  x  ", settings.get("QUOTED", "padded", String::new()).value);
+        assert_eq!("tab\tquote\"back\\slash\u{e8}\u{1F600}", settings.get("QUOTED", "escapes", String::new()).value);
+        assert_eq!("http://host/path?a=1&b=2", settings.get("QUOTED", "url", String::new()).value);
+        assert_eq!("YWJj==", settings.get("QUOTED", "base64", String::new()).value);
+        assert_eq!("x]", settings.get("QUOTED", "bracket", String::new()).value);
+
+        assert!(settings.set("QUOTED", "padded", " y ").is_ok());
+        assert!(settings.set("QUOTED", "url", "a#b").is_ok());
+        assert!(settings.set("QUOTED", "base64", "plain").is_ok());
+        assert!(settings.set_or_insert("QUOTED", "multi", "line1\nline2\u{1}").is_ok());
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        let saved = String::from_utf8(buffer).unwrap();
+        assert!(saved.contains("password = \"abc#123\" # inline comment\n"));
+        assert!(saved.contains("padded = \" y \"\n"));
+        assert!(saved.contains("url = \"a#b\"\n"));
+        assert!(saved.contains("base64 = plain\n"));
+        assert!(saved.contains("multi = \"line1\\nline2\\u0001\"\n"));
+        let reloaded: Settings = saved.parse().unwrap();
+        assert_eq!("line1\nline2\u{1}", reloaded.get("QUOTED", "multi", String::new()).value);
+        assert_eq!(" y ", reloaded.get("QUOTED", "padded", String::new()).value);
+
+        for bad_line in ["key = \"unterminated", "key = \"bad\\x\"", "key = 'a' b", "key = \"\\uD83D\""] {
+            let error = format!("[QUOTED]\n{}\n", bad_line).parse::<Settings>().err().unwrap();
+            assert!(matches!(error, SettingsError::BadQuotedValue { line: 2, .. }), "{}", bad_line);
+            assert_eq!(format!("Bad quoted value at line '2' of settings file: '{}'", STRING_SOURCE_NAME), error.to_string());
+        }
+    }
+
+    #[test]
+    fn multi_line_values() {
+        let content = concat!(
+            "[SQL]\n",
+            "query = SELECT * \\\n",
+            "    FROM users \\\n",
+            "  WHERE id = 1 # inline comment\n",
+            "path = C:\\temp\\\n",
+            "\n",
+            "[CERT]\n",
+            "pem = -----BEGIN-----\n",
+            "\tAAAA\n",
+            "\tBBBB # not a comment\n",
+            "\t-----END-----\n",
+            "next = 1\n");
+        let error = content.parse::<Settings>().err().unwrap();
+        assert!(matches!(error, SettingsError::MissingAssignTag { line: 3, .. }));
+
+        let mut settings = Settings::new().with_multi_line_values(MultiLineValues::Both);
+        assert_eq!(MultiLineValues::Both, settings.multi_line_values());
+        assert!(settings.load_from_reader(content.as_bytes(), "multi_line.ini").is_ok());
+        assert_eq!("SELECT * FROM users WHERE id = 1", settings.get("SQL", "query", String::new()).value);
+        assert_eq!("C:\\temp", settings.get("SQL", "path", String::new()).value);
+        assert_eq!("-----BEGIN-----\nAAAA\nBBBB # not a comment\n-----END-----", settings.get("CERT", "pem", String::new()).value);
+        assert_eq!(1, settings.get("CERT", "next", 0).value);
+        assert_eq!(Some(2..=4), settings.key_lines("SQL", "query"));
+        assert_eq!(Some(5..=6), settings.key_lines("SQL", "path"));
+        assert_eq!(Some(8..=11), settings.key_lines("CERT", "pem"));
+        assert_eq!(Some(12..=12), settings.key_lines("CERT", "next"));
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!(content, String::from_utf8(buffer).unwrap());
+
+        let settings_file_path = temp_copy("test_files/settings.ini", "multi_line_values");
+        settings = Settings::new().with_multi_line_values(MultiLineValues::Indented);
+        fs::write(&settings_file_path, &content[content.find("[CERT]").unwrap()..]).unwrap();
+        assert!(settings.load(&settings_file_path).is_ok());
+        assert!(settings.set("CERT", "pem", "-----BEGIN-----\nCCCC\n-----END-----").is_ok());
+        assert!(settings.set_or_insert("CERT", "sql", "SELECT *\nFROM users").is_ok());
+        assert!(settings.set_or_insert("CERT", "padded", "a\n b").is_ok());
+        assert!(settings.save().is_ok());
+        let saved = fs::read_to_string(&settings_file_path).unwrap();
+        assert_eq!(concat!(
+            "[CERT]\n",
+            "pem = -----BEGIN-----\n",
+            "\tCCCC\n",
+            "\t-----END-----\n",
+            "next = 1\n",
+            "sql = SELECT *\n",
+            "    FROM users\n",
+            "padded = \"a\\n b\"\n"), saved);
+        assert_eq!(Some(2..=4), settings.key_lines("CERT", "pem"));
+        assert_eq!(Some(5..=5), settings.key_lines("CERT", "next"));
+        assert_eq!(Some(6..=7), settings.key_lines("CERT", "sql"));
+        settings = Settings::new().with_multi_line_values(MultiLineValues::Indented);
+        assert!(settings.load(&settings_file_path).is_ok());
+        assert_eq!("SELECT *\nFROM users", settings.get("CERT", "sql", String::new()).value);
+        assert_eq!("a\n b", settings.get("CERT", "padded", String::new()).value);
+        fs::remove_file(&settings_file_path).unwrap();
+    }
+
+    #[test]
+    fn lists_and_arrays() {
+        let content = concat!(
+            "[SERVERS]\n",
+            "ports = 80, 443 ,8080\n",
+            "empty =\n",
+            "# hosts\n",
+            "host[] = alpha\n",
+            "host[] = beta # second\n",
+            "timeout = 10\n",
+            "host[] = gamma\n");
+        let error = content.parse::<Settings>().err().unwrap();
+        assert!(matches!(error, SettingsError::DuplicatedKey { line: 6, previous_line: 5, .. }));
+
+        let mut settings = Settings::new().with_array_keys(true);
+        assert!(settings.array_keys());
+        assert!(settings.load_from_reader(content.as_bytes(), "servers.ini").is_ok());
+        assert_eq!(vec![80u16, 443, 8080], settings.get_list::<u16>("SERVERS", "ports", ",").unwrap());
+        assert_eq!(Vec::<u16>::new(), settings.get_list::<u16>("SERVERS", "empty", ",").unwrap());
+        assert_eq!(vec!["alpha", "beta", "gamma"], settings.get_list::<String>("SERVERS", "host", ",").unwrap());
+        assert_eq!(vec!["alpha", "beta", "gamma"], settings.get_list::<String>("SERVERS", "host[]", ",").unwrap());
+        let error = settings.get_list::<u8>("SERVERS", "ports", ",").unwrap_err();
+        assert!(matches!(error, SettingsError::ListElementParsing { index: 1, line: 2, .. }));
+        let error = settings.get_list::<u8>("SERVERS", "host", ",").unwrap_err();
+        assert!(matches!(error, SettingsError::ListElementParsing { index: 0, line: 5, .. }));
+        assert_eq!(format!("Section 'SERVERS' key 'host' element '0', Parsing error: '{:#?}'", "alpha".parse::<u8>().unwrap_err()), error.to_string());
+        assert!(matches!(settings.get_list::<u8>("SERVERS", "goofy", ","), Err(SettingsError::KeyNotFound { .. })));
+        assert!(matches!(settings.get_list::<u8>("GOOFY", "ports", ","), Err(SettingsError::SectionNotFound { .. })));
+
+        assert!(settings.set_list("SERVERS", "ports", &[22, 80], ", ").is_ok());
+        assert!(settings.set_list("SERVERS", "host", &["delta", "epsilon"], ",").is_ok());
+        assert!(settings.set_list("SERVERS", "backup[]", &["x", "y"], ",").is_ok());
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!(concat!(
+            "[SERVERS]\n",
+            "ports = 22, 80\n",
+            "empty =\n",
+            "# hosts\n",
+            "host[] = delta\n",
+            "host[] = epsilon # second\n",
+            "timeout = 10\n",
+            "backup[] = x\n",
+            "backup[] = y\n"), String::from_utf8(buffer).unwrap());
+        assert!(settings.set_list("SERVERS", "host", &["a", "b", "c", "d"], ",").is_ok());
+        assert_eq!(vec!["a", "b", "c", "d"], settings.get_list::<String>("SERVERS", "host", ",").unwrap());
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert!(String::from_utf8(buffer).unwrap().contains("host[] = b # second\nhost[] = c\nhost[] = d\ntimeout = 10\n"));
+        assert!(settings.remove_key("SERVERS", "host[]", true).is_ok());
+        assert!(!settings.key_exists("SERVERS", "host[]"));
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!("[SERVERS]\nports = 22, 80\nempty =\ntimeout = 10\nbackup[] = x\nbackup[] = y\n", String::from_utf8(buffer).unwrap());
+    }
+
+    #[test]
+    fn env_interpolation() {
+        let content = concat!(
+            "[PATHS]\n",
+            "log_dir = ${HOME}/logs\n",
+            "level = ${LEVEL:-3}\n",
+            "empty = ${EMPTY:-default}\n",
+            "price = $$10 ${CURRENCY\n",
+            "hosts = ${HOST}, beta\n",
+            "missing = ${MISSING}/x\n",
+            "path = ${PATH:-none}\n");
+        let settings: Settings = content.parse().unwrap();
+        assert_eq!(EnvInterpolation::Disabled, *settings.env_interpolation());
+        assert_eq!("${HOME}/logs", settings.get("PATHS", "log_dir", String::new()).value);
+
+        let variables = HashMap::from([
+            ("HOME".to_string(), "/home/user".to_string()),
+            ("EMPTY".to_string(), String::new()),
+            ("HOST".to_string(), "alpha".to_string())]);
+        let mut settings = Settings::new().with_env_interpolation(EnvInterpolation::Map(variables));
+        assert!(settings.load_from_reader(content.as_bytes(), "paths.ini").is_ok());
+        assert_eq!("/home/user/logs", settings.get("PATHS", "log_dir", String::new()).value);
+        assert_eq!(3, settings.get("PATHS", "level", 0).value);
+        assert_eq!("default", settings.get("PATHS", "empty", String::new()).value);
+        assert_eq!("$10 ${CURRENCY", settings.get("PATHS", "price", String::new()).value);
+        assert_eq!(vec!["alpha", "beta"], settings.get_list::<String>("PATHS", "hosts", ",").unwrap());
+        assert_eq!("${HOME}/logs", settings.get_raw("PATHS", "log_dir").unwrap());
+        assert!(matches!(settings.get_raw("PATHS", "goofy"), Err(SettingsError::KeyNotFound { .. })));
+        let error = settings.get_value::<String>("PATHS", "missing").unwrap_err();
+        assert!(matches!(error, SettingsError::UndefinedVariable { line: 7, ref variable, .. } if variable == "MISSING"));
+        assert_eq!("Section 'PATHS' key 'missing', undefined environment variable 'MISSING'", error.to_string());
+
+        assert!(settings.set("PATHS", "level", "${LEVEL:-4}").is_ok());
+        assert_eq!(4, settings.get("PATHS", "level", 0).value);
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!(content.replace("${LEVEL:-3}", "${LEVEL:-4}"), String::from_utf8(buffer).unwrap());
+
+        settings = Settings::new().with_env_interpolation(EnvInterpolation::Process);
+        assert!(settings.load_from_reader(content.as_bytes(), "paths.ini").is_ok());
+        let path = std::env::var("PATH").ok().filter(|path| !path.is_empty()).unwrap_or("none".to_string());
+        assert_eq!(path, settings.get("PATHS", "path", String::new()).value);
+    }
+
+    #[test]
+    fn key_interpolation() {
+        let content = concat!(
+            "[PATHS]\n",
+            "base = /opt/app\n",
+            "logs = ${base}/logs\n",
+            "home = ${HOME}\n",
+            "tmp = ${TMP:-/tmp}\n",
+            "[LOG]\n",
+            "file = ${PATHS:logs}/app.log\n",
+            "missing = ${PATHS:goofy}\n",
+            "[CYCLE]\n",
+            "a = ${b}\n",
+            "b = x${LOOP:c}\n",
+            "[LOOP]\n",
+            "c = ${CYCLE:a}\n");
+        let mut settings = Settings::new().with_key_interpolation(true);
+        assert!(settings.key_interpolation());
+        assert!(settings.load_from_reader(content.as_bytes(), "app.ini").is_ok());
+        assert_eq!("/opt/app/logs", settings.get("PATHS", "logs", String::new()).value);
+        assert_eq!("/opt/app/logs/app.log", settings.get("LOG", "file", String::new()).value);
+        assert_eq!("/tmp", settings.get("PATHS", "tmp", String::new()).value);
+        let error = settings.get_value::<String>("LOG", "missing").unwrap_err();
+        assert!(matches!(error, SettingsError::UndefinedReference { line: 8, ref reference, .. } if reference == "PATHS:goofy"));
+        assert_eq!("Section 'LOG' key 'missing', undefined reference 'PATHS:goofy'", error.to_string());
+        assert!(matches!(settings.get_value::<String>("PATHS", "home"), Err(SettingsError::UndefinedReference { .. })));
+        let error = settings.get_value::<String>("CYCLE", "a").unwrap_err();
+        match &error {
+            SettingsError::InterpolationCycle { section, key, chain, .. } => {
+                assert_eq!(("CYCLE", "a"), (section.as_str(), key.as_str()));
+                let lines: Vec<usize> = chain.iter().map(|(_, _, line)| *line).collect();
+                assert_eq!(vec![10, 11, 13, 10], lines);
+            },
+            _ => panic!("{}", error)
+        }
+        assert_eq!(concat!("Section 'CYCLE' key 'a', interpolation cycle: CYCLE:a at line '10' -> ",
+            "CYCLE:b at line '11' -> LOOP:c at line '13' -> CYCLE:a at line '10'"), error.to_string());
+
+        // lazy resolution: the referenced value is read when the value is read
+        assert!(settings.set("PATHS", "base", "/srv").is_ok());
+        assert_eq!("/srv/logs/app.log", settings.get("LOG", "file", String::new()).value);
+        assert_eq!("${PATHS:logs}/app.log", settings.get_raw("LOG", "file").unwrap());
+
+        let variables = HashMap::from([("HOME".to_string(), "/home/user".to_string())]);
+        settings = Settings::new().with_key_interpolation(true).with_env_interpolation(EnvInterpolation::Map(variables));
+        assert!(settings.load_from_reader(content.as_bytes(), "app.ini").is_ok());
+        assert_eq!("/home/user", settings.get("PATHS", "home", String::new()).value);
+        assert_eq!("/opt/app/logs", settings.get("PATHS", "logs", String::new()).value);
+    }
+
+    #[test]
+    fn include_directives() {
+        let directory = std::env::temp_dir().join(format!("rssettings_{}_include", std::process::id()));
+        fs::create_dir_all(directory.join("include/conf.d")).unwrap();
+        for file in ["include.ini", "include/local.ini", "include/conf.d/10-db.ini",
+            "include/conf.d/20-db.ini", "include/conf.d/.disabled.ini"] {
+            fs::copy(Path::new("test_files").join(file), directory.join(file)).unwrap();
+        }
+        let main_path = directory.join("include.ini");
+        let local_path = directory.join("include/local.ini");
+        let db_path = directory.join("include/conf.d/10-db.ini");
+        let main_content = fs::read_to_string(&main_path).unwrap();
+
+        let mut settings = Settings::new();
+        assert!(settings.load(&main_path).is_ok());
+        assert_eq!("main", settings.get("GLOBAL", "name", String::new()).value);
+        assert!(settings.get("LOG", "enabled", false).value);
+        // later files override, hidden files are skipped
+        assert_eq!(3, settings.get("LOG", "level", 0).value);
+        assert_eq!("db.example.com", settings.get("DB", "host", String::new()).value);
+        assert_eq!(5432, settings.get("DB", "port", 0).value);
+        assert_eq!(main_path.to_str(), settings.key_file("LOG", "enabled"));
+        assert_eq!(local_path.to_str(), settings.key_file("LOG", "level"));
+        assert_eq!(directory.join("include/conf.d/20-db.ini").to_str(), settings.key_file("DB", "host"));
+        assert_eq!(db_path.to_str(), settings.key_file("DB", "port"));
+        assert_eq!(Some(3..=3), settings.key_lines("LOG", "level"));
+        assert_eq!(None, settings.key_file("DB", "goofy"));
+
+        // values are saved in their own files, unchanged files are not written
+        assert!(settings.set("LOG", "level", 5).is_ok());
+        assert!(settings.set_or_insert("DB", "user", "admin").is_ok());
+        assert!(settings.save().is_ok());
+        assert_eq!(main_content, fs::read_to_string(&main_path).unwrap());
+        assert_eq!("# local overrides\n[LOG]\nlevel = 5\n", fs::read_to_string(&local_path).unwrap());
+        assert_eq!("[DB]\nhost = localhost\nport = 5432\nuser = admin\n", fs::read_to_string(&db_path).unwrap());
+        let mut buffer = Vec::new();
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!(main_content, String::from_utf8(buffer).unwrap());
+
+        settings = Settings::new();
+        assert!(settings.load(&main_path).is_ok());
+        assert_eq!(5, settings.get("LOG", "level", 0).value);
+        assert_eq!("admin", settings.get("DB", "user", String::new()).value);
+
+        // a modified included file is a conflict
+        fs::write(&local_path, "[LOG]\nlevel = 7\n").unwrap();
+        assert!(settings.set("LOG", "enabled", false).is_ok());
+        let error = settings.save().err().unwrap();
+        assert!(matches!(error, SettingsError::Conflict { ref path, .. } if Some(path.as_str()) == local_path.to_str()));
+        drop(settings);
+
+        // included files can not follow the settings file to a new path
+        settings = Settings::new().with_drop_policy(DropPolicy::Never);
+        assert!(settings.load(&main_path).is_ok());
+        let copy_path = directory.join("copy.ini");
+        assert!(matches!(settings.save_as(&copy_path), Err(SettingsError::WritingFile { .. })));
+        assert!(!copy_path.exists());
+        // included files are written first, the settings file is kept if one of them fails
+        assert!(settings.set("LOG", "enabled", false).is_ok());
+        assert!(settings.set("LOG", "level", 8).is_ok());
+        fs::remove_file(&local_path).unwrap();
+        fs::create_dir(&local_path).unwrap();
+        let error = settings.save().err().unwrap();
+        assert!(matches!(error, SettingsError::WritingFile { ref path, .. } if Some(path.as_str()) == local_path.to_str()));
+        assert_eq!(main_content, fs::read_to_string(&main_path).unwrap());
+        drop(settings);
+
+        let cycle_path = directory.join("cycle.ini");
+        fs::write(&cycle_path, "[LOG]\n!include include/cycle.ini\n").unwrap();
+        fs::write(directory.join("include/cycle.ini"), "level = 1\n!include ../cycle.ini\n").unwrap();
+        settings = Settings::new();
+        let error = settings.load(&cycle_path).err().unwrap();
+        assert!(matches!(error, SettingsError::IncludeCycle { line: 2, ref include, .. } if include.ends_with("cycle.ini")));
+        assert!(!settings.is_initialize());
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn layered_settings() {
+        let system_path = std::env::temp_dir().join(format!("rssettings_{}_layered_system.ini", std::process::id()));
+        let user_path = std::env::temp_dir().join(format!("rssettings_{}_layered_user.ini", std::process::id()));
+        fs::write(&system_path, "[LOG]\nlevel = 2\nfile = /var/log/app.log\n").unwrap();
+        let _ = fs::remove_file(&user_path);
+
+        let defaults: Settings = "[LOG]\nenabled = false\nlevel = 1\n[DB]\nport = 5432\n".parse().unwrap();
+        let mut settings = LayeredSettings::new().with_layer("defaults", defaults, false);
+        assert!(settings.add_file_layer("system", &system_path, false).is_ok());
+        assert!(settings.add_file_layer("user", &user_path, true).is_ok());
+        settings.add_layer("override", "[LOG]\nenabled = true\n".parse().unwrap(), true);
+        assert_eq!(vec!["defaults", "system", "user", "override"], settings.layer_names());
+
+        assert!(settings.get("LOG", "enabled", false).value);
+        assert_eq!(2, settings.get("LOG", "level", 0).value);
+        assert_eq!(5432, settings.get("DB", "port", 0).value);
+        assert_eq!(Some("override"), settings.key_layer("LOG", "enabled"));
+        assert_eq!(Some("system"), settings.key_layer("LOG", "file"));
+        assert_eq!(None, settings.key_layer("LOG", "goofy"));
+        assert!(!settings.key_exists("LOG", "goofy"));
+        assert!(matches!(settings.get_value::<u8>("LOG", "goofy"), Err(SettingsError::KeyNotFound { .. })));
+        assert!(matches!(settings.get_value::<u8>("GOOFY", "level"), Err(SettingsError::SectionNotFound { .. })));
+        assert!(matches!(LayeredSettings::new().get_value::<u8>("LOG", "level"), Err(SettingsError::SectionNotFound { .. })));
+
+        assert!(settings.set("user", "LOG", "level", 4).is_ok());
+        assert_eq!(4, settings.get("LOG", "level", 0).value);
+        assert_eq!(Some("user"), settings.key_layer("LOG", "level"));
+        let error = settings.set("system", "LOG", "level", 5).err().unwrap();
+        assert!(matches!(error, SettingsError::InvalidLayer { ref layer, .. } if layer == "system"));
+        assert_eq!("Settings layer 'system' does not exist or is not writable", error.to_string());
+        assert!(matches!(settings.set("goofy", "LOG", "level", 5), Err(SettingsError::InvalidLayer { .. })));
+
+        // the missing user file is created, read only layers are not written
+        assert!(settings.save().is_ok());
+        assert_eq!("[LOG]\nlevel = 4\n", fs::read_to_string(&user_path).unwrap());
+        assert_eq!("[LOG]\nlevel = 2\nfile = /var/log/app.log\n", fs::read_to_string(&system_path).unwrap());
+
+        assert!(settings.remove_key("override", "LOG", "enabled").is_ok());
+        assert!(!settings.get("LOG", "enabled", true).value);
+        assert_eq!(Some("defaults"), settings.key_layer("LOG", "enabled"));
+        assert!(settings.layer("user").is_some_and(|user| user.key_exists("LOG", "level")));
+        assert!(settings.layer_mut("goofy").is_none());
+
+        // read only layers changed through layer_mut are not saved when dropped
+        assert!(settings.layer_mut("system").is_some_and(|system| system.set("LOG", "level", 9).is_ok()));
+        let defaults_path = std::env::temp_dir().join(format!("rssettings_{}_layered_defaults.ini", std::process::id()));
+        let mut defaults: Settings = "[LOG]\nlevel = 1\n".parse().unwrap();
+        assert!(defaults.save_as(&defaults_path).is_ok());
+        settings.add_layer("defaults", defaults, false);
+        assert!(settings.layer_mut("defaults").is_some_and(|defaults| defaults.set("LOG", "level", 9).is_ok()));
+        drop(settings);
+        assert_eq!("[LOG]\nlevel = 2\nfile = /var/log/app.log\n", fs::read_to_string(&system_path).unwrap());
+        assert_eq!("[LOG]\nlevel = 1\n", fs::read_to_string(&defaults_path).unwrap());
+        let _ = fs::remove_file(&defaults_path);
+        let _ = fs::remove_file(&system_path);
+        let _ = fs::remove_file(&user_path);
+    }
+
+    #[test]
+    fn env_overrides() {
+        let variables = vec![
+            ("APP__DATABASE__PORT".to_string(), "5433".to_string()),
+            ("APP__database__Host".to_string(), "db.example.com".to_string()),
+            ("APP__LOG__level".to_string(), "verbose".to_string()),
+            ("APP__SERVERS__ports".to_string(), "80, 8080".to_string()),
+            ("APP__DATABASE__url".to_string(), "${host}:${port}".to_string()),
+            ("APP_DATABASE_PORT".to_string(), "1".to_string()),
+            ("OTHER__DATABASE__PORT".to_string(), "2".to_string()),
+            ("APP__NOKEY".to_string(), "3".to_string())];
+        let env_overrides = EnvOverrides::from_variables("APP", "__", variables.clone());
+        assert_eq!(("APP", "__"), (env_overrides.prefix(), env_overrides.separator()));
+        assert_eq!(Some("APP__DATABASE__PORT"), env_overrides.variable("database", "port"));
+        assert_eq!(None, env_overrides.value("GLOBAL", "NOKEY"));
+        assert_eq!(None, EnvOverrides::from_variables("APP", "", variables.clone()).value("DATABASE", "PORT"));
+        assert_eq!(Some("2"), EnvOverrides::from_variables("", "__", variables).value("OTHER", "DATABASE__PORT"));
+
+        let settings_file_path = temp_copy("test_files/settings.ini", "env_overrides");
+        fs::write(&settings_file_path, "[DATABASE]\nhost = localhost\nport = 5432\n").unwrap();
+        let mut settings = Settings::new().with_env_overrides(env_overrides).with_key_interpolation(true);
+        assert!(settings.load(&settings_file_path).is_ok());
+        assert_eq!(5433, settings.get("DATABASE", "port", 0).value);
+        assert_eq!("db.example.com", settings.get("DATABASE", "host", String::new()).value);
+        assert_eq!("5433", settings.get_value::<String>("DATABASE", "port").unwrap());
+        assert_eq!("5432", settings.get_raw("DATABASE", "port").unwrap());
+        // missing keys and sections are overridden too
+        assert_eq!("db.example.com:5433", settings.get("DATABASE", "url", String::new()).value);
+        assert_eq!(vec![80, 8080], settings.get_list::<u16>("SERVERS", "ports", ",").unwrap());
+        let error = settings.get_value::<u8>("LOG", "level").unwrap_err();
+        assert!(matches!(error, SettingsError::Parsing { line: 0, .. }));
+        assert!(!settings.key_exists("LOG", "level"));
+
+        // overriding values are never saved
+        assert!(settings.set("DATABASE", "port", 6000).is_ok());
+        assert_eq!(5433, settings.get("DATABASE", "port", 0).value);
+        assert!(settings.save().is_ok());
+        assert_eq!("[DATABASE]\nhost = localhost\nport = 6000\n", fs::read_to_string(&settings_file_path).unwrap());
+        assert!(settings.env_overrides().value("DATABASE", "port").is_some());
+        let _ = fs::remove_file(&settings_file_path);
+    }
+
+    #[test]
+    fn arg_overrides() {
+        let variables = vec![("APP__LOG__level".to_string(), "2".to_string())];
+        let mut settings = Settings::new().with_env_overrides(EnvOverrides::from_variables("APP", "__", variables));
+        assert!(settings.load_from_reader("[LOG]\nlevel = 1\nfile = app.log\n[DB]\nhost = localhost\n".as_bytes(), "app.ini").is_ok());
+        assert_eq!(2, settings.get("LOG", "level", 0).value);
+        assert!(settings.apply_overrides(["LOG.level=5", "DB.host = db=1"], false).is_ok());
+        assert_eq!(5, settings.get("LOG", "level", 0).value);
+        assert_eq!(" db=1", settings.get_value::<String>("DB", "host").unwrap());
+        assert_eq!("localhost", settings.get_raw("DB", "host").unwrap());
+        assert!(settings.apply_overrides(vec![String::from("LOG.level=6")], false).is_ok());
+        assert_eq!(6, settings.get("LOG", "level", 0).value);
+
+        // invalid overrides are not applied
+        let error = settings.apply_overrides(["LOG.file=other.log", "LOG=7"], false).err().unwrap();
+        assert!(matches!(error, SettingsError::BadOverride { ref argument, .. } if argument == "LOG=7"));
+        assert_eq!("Bad override 'LOG=7', expected SECTION.key=value", error.to_string());
+        assert_eq!("app.log", settings.get("LOG", "file", String::new()).value);
+        assert!(matches!(settings.apply_overrides([".level=1"], false), Err(SettingsError::BadOverride { .. })));
+        assert!(matches!(settings.apply_overrides(["LOG.level"], false), Err(SettingsError::BadOverride { .. })));
+        assert!(matches!(settings.apply_overrides(["LOG.goofy=1"], false), Err(SettingsError::KeyNotFound { .. })));
+        assert!(matches!(settings.apply_overrides(["GOOFY.key=1"], false), Err(SettingsError::SectionNotFound { .. })));
+        assert!(settings.apply_overrides(["GOOFY.key.name=1"], true).is_ok());
+        assert_eq!(1, settings.get("GOOFY", "key.name", 0).value);
+        assert!(!settings.section_exists("GOOFY"));
+
+        let mut buffer = Vec::new();
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!("[LOG]\nlevel = 1\nfile = app.log\n[DB]\nhost = localhost\n", String::from_utf8(buffer).unwrap());
+        settings.clear_overrides();
+        assert_eq!(2, settings.get("LOG", "level", 0).value);
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn arg_override_matches() {
+        let command = clap::Command::new("app").arg(Settings::override_arg());
+        let matches = command.get_matches_from(["app", "--set", "LOG.level=5", "--set", "LOG.file=other.log"]);
+        let mut settings: Settings = "[LOG]\nlevel = 1\nfile = app.log\n".parse().unwrap();
+        assert!(settings.apply_override_matches(&matches, false).is_ok());
+        assert_eq!(5, settings.get("LOG", "level", 0).value);
+        assert_eq!("other.log", settings.get("LOG", "file", String::new()).value);
+        let matches = clap::Command::new("app").arg(Settings::override_arg()).get_matches_from(["app"]);
+        assert!(settings.apply_override_matches(&matches, false).is_ok());
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn schema_validation() {
+        let schema = Schema::load("test_files/settings.schema.ini").unwrap();
+        assert_eq!(Some(ValueType::U32), schema.section("GLOBAL").and_then(|section| section.key("u32_value")).map(|key| key.value_type()));
+        let mut settings = Settings::new();
+        assert!(settings.load("test_files/settings.ini").is_ok());
+        assert!(settings.validate(&schema).is_ok());
+
+        let schema = Schema::new().with_unknown_keys(false)
+            .with_section(SectionSchema::new("SERVER")
+                .with_key(KeySchema::new("port", ValueType::U16).with_min(1024.0))
+                .with_key(KeySchema::new("mode", ValueType::String).with_values(&["fast", "safe"]))
+                .with_key(KeySchema::new("host", ValueType::String).with_pattern("^[a-z.]+$").unwrap())
+                .with_key(KeySchema::new("workers", ValueType::U8))
+                .with_key(KeySchema::new("timeout", ValueType::U32).with_required(true))
+                .with_key(KeySchema::new("retries", ValueType::U8).with_required(true).with_default("3")))
+            .with_section(SectionSchema::new("DB")
+                .with_key(KeySchema::new("host", ValueType::String).with_required(true))
+                .with_key(KeySchema::new("port", ValueType::U16).with_required(true)));
+        assert_eq!(Some("3"), schema.default_value("SERVER", "retries"));
+        assert!(!schema.unknown_keys());
+        let content = "[SERVER]\nport = 80\nmode = turbo\nhost = Bad Host\nworkers = many\ndebug = true\n[EXTRA]\nx = 1\n";
+        let mut settings: Settings = content.parse().unwrap();
+        let error = settings.validate(&schema).err().unwrap();
+        let errors = match &error {
+            SettingsError::SchemaViolations { errors, .. } => errors,
+            _ => panic!("{}", error)
+        };
+        assert_eq!(8, errors.len());
+        assert!(matches!(errors[0], SettingsError::ValueOutOfRange { line: 2, ref value, .. } if value == "80"));
+        assert!(matches!(errors[1], SettingsError::ValueNotAllowed { line: 3, ref value, .. } if value == "turbo"));
+        assert!(matches!(errors[2], SettingsError::PatternMismatch { line: 4, .. }));
+        assert!(matches!(errors[3], SettingsError::Parsing { line: 5, .. }));
+        assert!(matches!(errors[4], SettingsError::KeyNotFound { ref key, .. } if key == "timeout"));
+        assert!(matches!(errors[5], SettingsError::SectionNotFound { ref section, .. } if section == "DB"));
+        assert!(matches!(errors[6], SettingsError::UnknownKey { line: 6, ref key, .. } if key == "debug"));
+        assert!(matches!(errors[7], SettingsError::UnknownSection { line: 7, ref section, .. } if section == "EXTRA"));
+        assert!(error.to_string().starts_with("Settings '<string>' do not match the schema: Section 'SERVER' key 'port', value '80' out of range '1024..' at line '2'; "));
+
+        // values are checked as they are read
+        assert!(settings.apply_overrides(["SERVER.port=8080", "SERVER.workers=4", "SERVER.mode=safe", "SERVER.host=localhost",
+            "SERVER.timeout=10", "DB.host=db", "DB.port=5432"], true).is_ok());
+        assert!(settings.validate(&schema.clone().with_unknown_keys(true)).is_ok());
+        settings.clear_overrides();
+        assert!(settings.apply_overrides(["SERVER.port=8080", "SERVER.workers=4", "SERVER.mode=safe", "SERVER.host=localhost",
+            "SERVER.timeout=10", "DB.host=db", "DB.port=70000"], true).is_ok());
+        let error = settings.validate(&schema.with_unknown_keys(true)).err().unwrap();
+        assert!(matches!(error, SettingsError::SchemaViolations { ref errors, .. }
+            if errors.len() == 1 && matches!(errors[0], SettingsError::Parsing { line: 0, .. })));
+
+        for (text, line) in [("[LOG]\nlevel.kind = u8\n", 2), ("[LOG]\n\nlevel.type = u9\n", 3),
+            ("level.pattern = \"(\"\n", 1), ("[LOG]\nlevel = 1\n", 2)] {
+            let error = text.parse::<Schema>().err().unwrap();
+            assert!(matches!(error, SettingsError::InvalidSchema { line: error_line, .. } if error_line == line), "{}", error);
+        }
+        assert!(matches!(KeySchema::new("host", ValueType::String).with_pattern("("), Err(SettingsError::InvalidSchema { .. })));
+
+        let schema = Schema::new()
+            .with_section(SectionSchema::new("LOG").with_key(KeySchema::new("level", ValueType::U8).with_default("3"))
+                .with_key(KeySchema::new("Level", ValueType::U8).with_default("4")));
+        assert_eq!(NameMatching::Exact, schema.name_matching());
+        assert_eq!(Some("3"), schema.default_value("LOG", "level"));
+        assert_eq!(None, schema.default_value("log", "Level"));
+        let schema = schema.with_name_matching(NameMatching::AsciiCaseInsensitive);
+        assert_eq!(Some("4"), schema.default_value("log", "LEVEL"));
+        assert!(schema.section("Log").is_some_and(|section| section.key("level").is_some()));
+        let schema = schema.with_section(SectionSchema::new("Log").with_key(KeySchema::new("file", ValueType::String)));
+        assert_eq!(None, schema.default_value("LOG", "level"));
+        assert!(schema.section("LOG").is_some_and(|section| section.key("FILE").is_some()));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn schema_template() {
+        let schema = Schema::load("test_files/settings.schema.ini").unwrap();
+        assert_eq!(Some("Values of every type"), schema.section("GLOBAL").and_then(|section| section.description()));
+        let template = Settings::schema_template(&schema);
+        assert!(template.starts_with("# Values of every type\n[GLOBAL]\n# type: bool, required\n# bool_value =\n"));
+        assert!(template.contains("\n# An unsigned value\n# type: u32, required, range: ..=1000\nu32_value = 42\n"));
+        assert!(template.ends_with("\n# type: string, pattern: ^The\n# string_value =\n"));
+
+        let schema = Schema::new()
+            .with_section(SectionSchema::new("SERVER").with_description("Server\nsettings")
+                .with_key(KeySchema::new("name", ValueType::String).with_default(" main #1"))
+                .with_key(KeySchema::new("mode", ValueType::String).with_default("safe").with_values(&["fast", "safe"])))
+            .with_section(SectionSchema::new("LOG"));
+        let template = Settings::schema_template(&schema);
+        assert_eq!("# Server\n# settings\n[SERVER]\n# type: string\nname = \" main #1\"\n# type: string, values: fast,safe\nmode = safe\n\n[LOG]\n", template);
+
+        let path = std::env::temp_dir().join(format!("rssettings_{}_template.ini", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut settings = Settings::new();
+        assert!(settings.load_or_create(&path, &schema).is_ok());
+        assert_eq!(template, fs::read_to_string(&path).unwrap());
+        assert_eq!(" main #1", settings.get("SERVER", "name", String::new()).value);
+        assert!(settings.validate(&schema).is_ok());
+        // an existing file is loaded as it is
+        fs::write(&path, "[SERVER]\nmode = fast\n").unwrap();
+        let mut settings = Settings::new();
+        assert!(settings.load_or_create(&path, &schema).is_ok());
+        assert_eq!("fast", settings.get("SERVER", "mode", String::new()).value);
+        assert!(!settings.key_exists("SERVER", "name"));
+        assert_eq!("[SERVER]\nmode = fast\n", fs::read_to_string(&path).unwrap());
+        // the template is written through a temporary file that is removed
+        let temp_prefix = format!(".rssettings_{}_template.ini.", std::process::id());
+        assert!(!fs::read_dir(std::env::temp_dir()).unwrap()
+            .any(|entry| entry.unwrap().file_name().to_string_lossy().starts_with(&temp_prefix)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn trailing_backslash_round_trip() {
+        for multi_line_values in [MultiLineValues::Disabled, MultiLineValues::Backslash, MultiLineValues::Both] {
+            let path = std::env::temp_dir().join(format!("rssettings_{}_backslash_{:?}.ini", std::process::id(), multi_line_values));
+            fs::write(&path, "[A]\nk = 1\nnext = 2\n").unwrap();
+            let mut settings = Settings::new().with_drop_policy(DropPolicy::Never).with_multi_line_values(multi_line_values);
+            assert!(settings.load(&path).is_ok());
+            assert!(settings.set("A", "k", "C:\\dir\\").is_ok());
+            assert!(settings.save().is_ok());
+            assert_eq!("[A]\nk = \"C:\\\\dir\\\\\"\nnext = 2\n", fs::read_to_string(&path).unwrap());
+            let mut reloaded = Settings::new().with_drop_policy(DropPolicy::Never).with_multi_line_values(multi_line_values);
+            assert!(reloaded.load(&path).is_ok());
+            assert_eq!("C:\\dir\\", reloaded.get("A", "k", String::new()).value);
+            assert_eq!(2, reloaded.get("A", "next", 0).value);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn name_matching() {
+        let text = "[Log]\nEnabled = true\n\n[LOG]\nlevel = 1\n";
+        let settings: Settings = text.parse().unwrap();
+        assert_eq!(NameMatching::Exact, settings.name_matching());
+        assert!(!settings.key_exists("LOG", "enabled"));
+        assert!(settings.key_exists("Log", "Enabled"));
+
+        let mut settings = Settings::new().with_name_matching(NameMatching::AsciiCaseInsensitive);
+        assert!(settings.load_from_reader(text.as_bytes(), "log.ini").is_ok());
+        assert!(settings.get("log", "ENABLED", false).value);
+        assert_eq!(1, settings.get("Log", "Level", 0).value);
+        assert!(!settings.section_exists("LÒG"));
+        assert!(settings.set("LOG", "enabled", false).is_ok());
+        assert!(settings.set_or_insert("log", "File", "app.log").is_ok());
+        assert!(settings.apply_overrides(["lOg.LEVEL=3"], false).is_ok());
+        assert_eq!(3, settings.get("LOG", "level", 0).value);
+        let mut buffer: Vec<u8> = vec![];
+        assert!(settings.write_to(&mut buffer).is_ok());
+        assert_eq!("[Log]\nEnabled = false\n\n[LOG]\nlevel = 1\nFile = app.log\n", String::from_utf8(buffer).unwrap());
+        assert!(settings.remove_key("LOG", "file", false).is_ok());
+        assert!(settings.remove_section("log", false).is_ok());
+        assert!(!settings.section_exists("Log"));
+
+        let mut settings = Settings::new().with_name_matching(NameMatching::AsciiCaseInsensitive);
+        let error = settings.load_from_reader("[LOG]\nlevel = 1\n[log]\nLEVEL = 2\n".as_bytes(), "log.ini").err().unwrap();
+        assert!(matches!(error, SettingsError::DuplicatedKey { line: 4, previous_line: 2, .. }));
+
+        let text = "[ÀREA]\nCittà = Roma\n";
+        let mut settings = Settings::new().with_name_matching(NameMatching::AsciiCaseInsensitive);
+        assert!(settings.load_from_reader(text.as_bytes(), "area.ini").is_ok());
+        assert!(!settings.key_exists("àrea", "CITTÀ"));
+        let mut settings = Settings::new().with_name_matching(NameMatching::UnicodeCaseFold);
+        assert!(settings.load_from_reader(text.as_bytes(), "area.ini").is_ok());
+        assert_eq!("Roma", settings.get("àrea", "CITTÀ", String::new()).value);
+    }
+
+    #[test]
+    fn name_matching_reload() {
+        let path = std::env::temp_dir().join(format!("rssettings_{}_name_matching.ini", std::process::id()));
+        fs::write(&path, "[Log]\nLevel = 1\n").unwrap();
+        let mut settings = Settings::new().with_drop_policy(DropPolicy::Never).with_name_matching(NameMatching::AsciiCaseInsensitive);
+        assert!(settings.load(&path).is_ok());
+        assert_eq!(1, settings.get("LOG", "level", 0).value);
+        fs::write(&path, "[Log]\nLevel = 2\n[LOG]\nenabled = true\n").unwrap();
+        assert!(settings.reload().is_ok());
+        assert_eq!(2, settings.get("LOG", "level", 0).value);
+        assert!(settings.get("log", "ENABLED", false).value);
+        fs::write(&path, "[Log]\nLevel = 2\n[LOG]\nlevel = 3\n").unwrap();
+        assert!(matches!(settings.reload(), Err(SettingsError::DuplicatedKey { line: 4, previous_line: 2, .. })));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn nested_sections() {
+        let text = "name = app\nlog.level = 2\n[server]\ntimeout = 30\nhttp.port = 80\n[server.http]\nhost = localhost\n\
+            [server.https]\nport = 443\n[server.tls.certs]\nfile = cert.pem\n";
+        let settings: Settings = text.parse().unwrap();
+        assert_eq!(vec!["log", "server"], settings.child_sections(""));
+        assert_eq!(vec!["server.http", "server.https", "server.tls"], settings.child_sections("server"));
+        assert!(settings.child_sections("server.http").is_empty());
+        assert_eq!(Some(("server", "http.port")), settings.resolve_path("server.http.port"));
+        assert_eq!(Some(("server.http", "host")), settings.resolve_path("server.http.host"));
+        assert_eq!(Some((GLOBAL_SECTION, "log.level")), settings.resolve_path("log.level"));
+        assert_eq!("app", settings.get_path_value::<String>("name").unwrap());
+        assert_eq!(443, settings.get_path_value::<u16>("server.https.port").unwrap());
+        assert!(matches!(settings.get_path_value::<u16>("server.ftp.port"), Err(SettingsError::SectionNotFound { ref section, .. }) if section == "server.ftp"));
+        assert!(matches!(settings.get_path_value::<u16>("server.http.user"), Err(SettingsError::KeyNotFound { ref key, .. }) if key == "user"));
+
+        let root = settings.tree();
+        assert_eq!("", root.path());
+        assert_eq!(vec!["name"], root.keys());
+        assert_eq!(2, root.get("log.level", 0).value);
+        let server = settings.subtree("server").unwrap();
+        assert_eq!(vec!["http", "https", "tls"], server.children());
+        assert_eq!(vec!["timeout"], server.keys());
+        assert!(server.key_exists("http.host"));
+        assert!(!server.key_exists("http.user"));
+        let http = server.child("http").unwrap();
+        assert_eq!("server.http", http.path());
+        assert_eq!(vec!["port", "host"], http.keys());
+        assert_eq!(80, http.get("port", 0).value);
+        let certs = server.child("tls.certs").unwrap();
+        assert_eq!("cert.pem", certs.get_value::<String>("file").unwrap());
+        assert!(settings.subtree("server.tls").unwrap().keys().is_empty());
+        assert!(settings.subtree("server.ftp").is_none());
+        assert!(settings.subtree("server.http.port").is_none());
+
+        let mut settings = Settings::new().with_name_matching(NameMatching::AsciiCaseInsensitive);
+        assert!(settings.load_from_reader(text.as_bytes(), "server.ini").is_ok());
+        assert_eq!(Some(("server", "http.port")), settings.resolve_path("SERVER.Http.PORT"));
+        assert_eq!(vec!["server.http", "server.https", "server.tls"], settings.child_sections("Server"));
+        assert!(settings.apply_overrides(["server.https.port=8443"], false).is_ok());
+        assert_eq!(8443, settings.subtree("SERVER").unwrap().get("HTTPS.port", 0).value);
+    }
+
+    #[test]
+    fn iteration() {
+        let text = "name = app\n[LOG]\nenabled = true\nlevel = ${name}\n[SERVERS]\nhost[] = alpha\nhost[] = beta, gamma\n";
+        let mut settings = Settings::new().with_array_keys(true).with_key_interpolation(true);
+        assert!(settings.load_from_reader(text.as_bytes(), "app.ini").is_ok());
+        assert_eq!(vec![GLOBAL_SECTION, "LOG", "SERVERS"], settings.sections().collect::<Vec<&str>>());
+        assert_eq!(vec!["enabled", "level"], settings.keys("LOG").collect::<Vec<&str>>());
+        assert_eq!(vec!["host[]", "host[]"], settings.keys("SERVERS").collect::<Vec<&str>>());
+        assert_eq!(0, settings.keys("DB").count());
+        assert!(settings.apply_overrides(["LOG.enabled=false"], false).is_ok());
+        assert_eq!(vec![("enabled", "true", 3), ("level", "${name}", 4)], settings.entries("LOG").collect::<Vec<(&str, &str, usize)>>());
+        assert!(settings.set_or_insert("LOG", "file", "app.log").is_ok());
+        assert_eq!(Some(("file", "app.log", 0)), settings.entries("LOG").last());
+
+        let map = settings.to_map();
+        assert_eq!(vec![GLOBAL_SECTION, "LOG", "SERVERS"], map.keys().collect::<Vec<&String>>());
+        assert_eq!("app", map[GLOBAL_SECTION]["name"]);
+        assert_eq!("${name}", map["LOG"]["level"]);
+        assert_eq!("app.log", map["LOG"]["file"]);
+        // every array element has its own indexed key
+        assert_eq!(vec!["host[0]", "host[1]"], map["SERVERS"].keys().collect::<Vec<&String>>());
+        assert_eq!("alpha", map["SERVERS"]["host[0]"]);
+        assert_eq!("beta, gamma", map["SERVERS"]["host[1]"]);
+    }
 
     #[test]
     fn section_or_key_exists() {
         let mut settings = Settings::new();
-        assert_eq!(StdResult::Ok(()), settings.load("test_files/settings.ini"));
-        assert_eq!(false, settings.section_exists("GOOFY"));
-        assert_eq!(true, settings.section_exists(GLOBAL_SECTION));
-        assert_eq!(false, settings.key_exists(GLOBAL_SECTION, "enabled"));
-        assert_eq!(true, settings.key_exists(GLOBAL_SECTION, "string_value"));
+        assert!(settings.load("test_files/settings.ini").is_ok());
+        assert!(!settings.section_exists("GOOFY"));
+        assert!(settings.section_exists(GLOBAL_SECTION));
+        assert!(!settings.key_exists(GLOBAL_SECTION, "enabled"));
+        assert!(settings.key_exists(GLOBAL_SECTION, "string_value"));
     }
 
     #[test]
@@ -1109,32 +4653,52 @@ key: key1, value: def
             "Sezione '{}' chiave '{}', Errore di analisi: '{}'",
             "Errore scrittura file: '{}': '{}'",
             "Errore lettura file: '{}' alla line {}: '{}'",
-            "Settings già inizializzato utilizzando il file: '{}'"
+            "Settings già inizializzato utilizzando il file: '{}'",
+            "Nome di sezione o chiave non valido: '{}'",
+            "Il file di settings '{}' è stato modificato dopo il caricamento o il salvataggio",
+            "Errore serde: '{}'",
+            "La sezione '{}' ha chiavi mancanti o non valide: {}",
+            "{} alla linea '{}'",
+            "Valore tra virgolette non valido alla linea '{}' del file di settings: '{}'",
+            "Sezione '{}' chiave '{}' elemento '{}', Errore di analisi: '{}'",
+            "Sezione '{}' chiave '{}', variabile d'ambiente '{}' non definita",
+            "Sezione '{}' chiave '{}', riferimento '{}' non definito",
+            "Sezione '{}' chiave '{}', ciclo di interpolazione: {}",
+            "Inclusione ciclica alla linea '{}' del file di settings '{}': '{}'",
+            "Il livello di settings '{}' non esiste o non è scrivibile",
+            "Override '{}' errato, atteso SEZIONE.chiave=valore",
+            "Sezione '{}' chiave '{}', valore '{}' fuori dall'intervallo '{}'",
+            "Sezione '{}' chiave '{}', valore '{}' non corrisponde a '{}'",
+            "Sezione '{}' chiave '{}', valore '{}' non è tra '{}'",
+            "Sezione '{}' non dichiarata nello schema",
+            "Sezione '{}' chiave '{}' non dichiarata nello schema",
+            "Schema '{}' non valido alla linea '{}': '{}'",
+            "Settings '{}' non conformi allo schema: {}"
         ];
         
 
         let mut settings = Settings::new_locale_messages(&IT_SETTINGS_MESSAGES);
         let ioerror = File::open("goofy.ini").err().unwrap();
         let os_message = format!("{:#}", ioerror);
-        assert_eq!(Result::Err(format!("Errore apertura file di settings: 'goofy.ini': '{}'", os_message)), settings.load("goofy.ini"));
-        assert_eq!(Result::Err("Manca il tag di inizio sezione '[' alla linea '7' del file di settings: 'test_files/missing_start_section_tag.ini'".to_string()), settings.load("test_files/missing_start_section_tag.ini"));
-        assert_eq!(Result::Err("Manca il tag di fine sezione ']' alla linea '11' del file di settings: 'test_files/missing_end_section_tag.ini'".to_string()), settings.load("test_files/missing_end_section_tag.ini"));
-        assert_eq!(Result::Err("Manca il tag di assegnazione '=' alla linea '3' del file di settings: 'test_files/missing_assign_tag.ini'".to_string()), settings.load("test_files/missing_assign_tag.ini"));
-        assert_eq!(Result::Err("Manca la chiave alla linea '5' del file di settings: 'test_files/missing_key.ini'".to_string()), settings.load("test_files/missing_key.ini"));
-        assert_eq!(Result::Err("Chiave duplicata 'key1' alla linea '5' precedentemente definita alla linea '2' del file di settings: 'test_files/duplicated_key.ini'".to_string()), settings.load("test_files/duplicated_key.ini"));
-        assert_eq!(Result::Ok(()), settings.load("test_files/set_get_errors.ini"));
-        assert_eq!("Sezione 'GENERALE' non trovata".to_string(), settings.get("GENERALE", "enabled", false).error);
-        assert_eq!("Sezione 'GENERAL' chiave 'enable' non trovata".to_string(), settings.get("GENERAL", "enable", true).error);
-        assert_eq!("Sezione 'GENERAL' chiave 'float_value', Errore di analisi: 'ParseFloatError {\n    kind: Invalid,\n}'".to_string(), settings.get("GENERAL", "float_value", -1.0f32).error);
-        assert_eq!(Result::Err("Settings già inizializzato utilizzando il file: 'test_files/set_get_errors.ini'".to_string()), settings.load("test_files/settings.ini"));
-        assert_eq!(true , settings.get("LOG", "enabled", false).value);
+        assert_eq!(format!("Errore apertura file di settings: 'goofy.ini': '{}'", os_message), settings.load("goofy.ini").unwrap_err().to_string());
+        assert_eq!("Manca il tag di inizio sezione '[' alla linea '7' del file di settings: 'test_files/missing_start_section_tag.ini'".to_string(), settings.load("test_files/missing_start_section_tag.ini").unwrap_err().to_string());
+        assert_eq!("Manca il tag di fine sezione ']' alla linea '11' del file di settings: 'test_files/missing_end_section_tag.ini'".to_string(), settings.load("test_files/missing_end_section_tag.ini").unwrap_err().to_string());
+        assert_eq!("Manca il tag di assegnazione '=' alla linea '3' del file di settings: 'test_files/missing_assign_tag.ini'".to_string(), settings.load("test_files/missing_assign_tag.ini").unwrap_err().to_string());
+        assert_eq!("Manca la chiave alla linea '5' del file di settings: 'test_files/missing_key.ini'".to_string(), settings.load("test_files/missing_key.ini").unwrap_err().to_string());
+        assert_eq!("Chiave duplicata 'key1' alla linea '5' precedentemente definita alla linea '2' del file di settings: 'test_files/duplicated_key.ini'".to_string(), settings.load("test_files/duplicated_key.ini").unwrap_err().to_string());
+        assert!(settings.load("test_files/set_get_errors.ini").is_ok());
+        assert_eq!("Sezione 'GENERALE' non trovata".to_string(), settings.get("GENERALE", "enabled", false).error.unwrap().to_string());
+        assert_eq!("Sezione 'GENERAL' chiave 'enable' non trovata".to_string(), settings.get("GENERAL", "enable", true).error.unwrap().to_string());
+        assert_eq!("Sezione 'GENERAL' chiave 'float_value', Errore di analisi: 'ParseFloatError {\n    kind: Invalid,\n}'".to_string(), settings.get("GENERAL", "float_value", -1.0f32).error.unwrap().to_string());
+        assert_eq!("Settings già inizializzato utilizzando il file: 'test_files/set_get_errors.ini'".to_string(), settings.load("test_files/settings.ini").unwrap_err().to_string());
+        assert!(settings.get("LOG", "enabled", false).value);
     }
 
     #[test]
     fn multi_thread_settings() {
         let settings_file_path ="test_files/settings.ini";
         let mut settings = Settings::new();
-        assert_eq!(Result::Ok(()), settings.load(settings_file_path));
+        assert!(settings.load(settings_file_path).is_ok());
 
         let settings= Arc::new(Mutex::new(settings));
         let thread1_settings = settings.clone(); 
@@ -1156,16 +4720,16 @@ key: key1, value: def
             print!(".");
             std::io::stdout().flush().unwrap_or(());
             if 0 == cnt % 4 {
-                println!("");
+                println!();
             }
-            cnt = cnt + 1;
+            cnt += 1;
         }
-        println!("");
+        println!();
         thread_handler.join().unwrap_or(());
         {
-            assert!(Result::Ok(()) == settings.lock().unwrap().set(GLOBAL_SECTION, "bool_value", true));
+            assert!(settings.lock().unwrap().set(GLOBAL_SECTION, "bool_value", true).is_ok());
         }
         let result = settings.lock().unwrap().get(GLOBAL_SECTION, "bool_value", false); 
-        assert!(result.error.len() == 0 && true == result.value);
+        assert!(result.error.is_none() && result.value);
     }
 }
@@ -0,0 +1,289 @@
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+use crate::{Section, Settings, SettingsError, SettingsValue, GLOBAL_SECTION};
+
+// Module giving a hierarchical view of Settings: dotted section names
+// (i.g. [server.http]) and dotted keys (i.g. "http.port = 80" in [server])
+// form a tree whose nodes and keys are addressed by dotted paths
+// (i.g. "server.http.port"), the keys of the GLOBAL section are the keys of the root.
+
+// Separator of the names in a path
+const PATH_SEPARATOR: char = '.';
+
+/// A node of the tree formed by dotted section names and dotted keys, see Settings::tree
+/// It borrows Settings: paths are resolved and values read from Settings when they are asked
+/// # Examples
+/// ```
+/// use rssettings::Settings;
+///
+/// fn main() {
+///     let text = "[server]\ntimeout = 30\nhttp.port = 80\n[server.http]\nhost = localhost\n[server.https]\nport = 443\n";
+///     let settings: Settings = text.parse().unwrap();
+///     let server = settings.subtree("server").unwrap();
+///     assert_eq!(vec!["http", "https"], server.children());
+///     assert_eq!(vec!["timeout"], server.keys());
+///     let http = server.child("http").unwrap();
+///     assert_eq!(vec!["port", "host"], http.keys());
+///     assert_eq!(80, http.get("port", 0).value);
+///     assert_eq!(443, server.get("https.port", 0).value);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SettingsTree<'a> {
+    settings: &'a Settings,
+    path: String,
+}
+
+impl<'a> SettingsTree<'a> {
+    /// Returns the path of the node, empty for the root
+    ///  * `self` -  An immutable reference to SettingsTree struct
+    ///
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the names of the child nodes as they are written in the settings file
+    ///  * `self` -  An immutable reference to SettingsTree struct
+    ///
+    pub fn children(&self) -> Vec<&'a str> {
+        let depth = path_segments(&self.path).len();
+        self.settings.child_nodes(&self.path).into_iter().map(|child| child[depth]).collect()
+    }
+
+    /// Returns a node below this one, None if no section or key is below it
+    ///  * `self` -  An immutable reference to SettingsTree struct
+    ///  * `path` -  A string slice that holds the path of the node relative to this one (i.g. "http" or "tls.certs")
+    ///
+    pub fn child(&self, path: &str) -> Option<SettingsTree<'a>> {
+        self.settings.subtree(&self.full_path(path))
+    }
+
+    /// Returns the keys of the node as they are written in the settings file,
+    /// the keys of a section and the last names of the dotted keys of its parents
+    ///  * `self` -  An immutable reference to SettingsTree struct
+    ///
+    pub fn keys(&self) -> Vec<&'a str> {
+        let node = path_segments(&self.path);
+        let mut keys: Vec<&str> = vec![];
+        for (segments, key) in self.settings.tree_entries() {
+            if let Some(key) = key {
+                let in_node = segments.len() == node.len() && self.settings.is_path_prefix(&node, &segments);
+                if in_node && !keys.iter().any(|found| self.settings.name_matching.matches(found, key)) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys
+    }
+
+    /// Returns true if a key below the node exists, see Settings::resolve_path
+    ///  * `self` -  An immutable reference to SettingsTree struct
+    ///  * `path` -  A string slice that holds the path of the key relative to the node (i.g. "http.port")
+    ///
+    pub fn key_exists(&self, path: &str) -> bool {
+        self.settings.resolve_path(&self.full_path(path)).is_some()
+    }
+
+    /// Generic method use to get the value of a key below the node,
+    /// see Settings::get and Settings::get_path_value
+    ///  * `self` -  An immutable reference to SettingsTree struct
+    ///  * `path` -  A string slice that holds the path of the key relative to the node (i.g. "http.port")
+    ///  * `T` -  A default generic value returned in case an error occurs
+    ///
+    pub fn get<T: FromStr + Display>(&self, path: &str, default_value: T) -> SettingsValue<T> where <T as FromStr>::Err: Debug {
+        match self.get_value(path) {
+            Ok(value) => SettingsValue { value, error: None },
+            Err(error) => SettingsValue { value: default_value, error: Some(error) }
+        }
+    }
+
+    /// Generic method use to get the value of a key below the node
+    /// without a default value, see Settings::get_path_value
+    ///  * `self` -  An immutable reference to SettingsTree struct
+    ///  * `path` -  A string slice that holds the path of the key relative to the node (i.g. "http.port")
+    ///
+    pub fn get_value<T: FromStr>(&self, path: &str) -> Result<T, SettingsError> where <T as FromStr>::Err: Debug {
+        self.settings.get_path_value(&self.full_path(path))
+    }
+
+    // Returns the path of a node or key relative to this node
+    fn full_path(&self, path: &str) -> String {
+        if self.path.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}{}{}", self.path, PATH_SEPARATOR, path)
+        }
+    }
+}
+
+impl Settings {
+    /// Returns the root of the tree formed by dotted section names and dotted keys,
+    /// see SettingsTree
+    ///  * `self` -  An immutable reference to Setting struct
+    ///
+    pub fn tree(&self) -> SettingsTree<'_> {
+        SettingsTree { settings: self, path: String::new() }
+    }
+
+    /// Returns a node of the tree formed by dotted section names and dotted keys,
+    /// None if no section or key is below the path
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    ///
+    /// fn main() {
+    ///     let settings: Settings = "[server.tls.certs]\nfile = cert.pem\n".parse().unwrap();
+    ///     let tls = settings.subtree("server.tls").unwrap();
+    ///     assert_eq!("cert.pem", tls.get("certs.file", String::new()).value);
+    ///     assert!(settings.subtree("server.ftp").is_none());
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `path` -  A string slice that holds the path of the node, empty for the root
+    ///
+    pub fn subtree(&self, path: &str) -> Option<SettingsTree<'_>> {
+        let node = path_segments(path);
+        let exists = node.is_empty() || self.tree_entries().iter().any(|(segments, _)| self.is_path_prefix(&node, segments));
+        exists.then(|| SettingsTree { settings: self, path: path.to_string() })
+    }
+
+    /// Returns the paths of the child nodes of a node as they are written in the settings file
+    /// (i.g. "server.http" and "server.https" for "server"), the child nodes of the root for
+    /// an empty path; a node is a child even if it is only the parent of a section or a dotted key
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    ///
+    /// fn main() {
+    ///     let settings: Settings = "[server.http]\nport = 80\n[server.tls.certs]\nfile = cert.pem\n".parse().unwrap();
+    ///     assert_eq!(vec!["server"], settings.child_sections(""));
+    ///     assert_eq!(vec!["server.http", "server.tls"], settings.child_sections("server"));
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `path` -  A string slice that holds the path of the node
+    ///
+    pub fn child_sections(&self, path: &str) -> Vec<String> {
+        self.child_nodes(path).into_iter().map(|child| child.join(&PATH_SEPARATOR.to_string())).collect()
+    }
+
+    /// Returns section and key holding the value of a dotted path as they are written in the
+    /// settings file, None if no key is at the path
+    /// The longest section name is tried first: "server.http.port" is the key "port" of the
+    /// section [server.http], the key "http.port" of the section [server] otherwise;
+    /// a path without sections is a key of the GLOBAL section
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    ///
+    /// fn main() {
+    ///     let settings: Settings = "[server]\nhttp.port = 80\n[server.https]\nport = 443\n".parse().unwrap();
+    ///     assert_eq!(Some(("server", "http.port")), settings.resolve_path("server.http.port"));
+    ///     assert_eq!(Some(("server.https", "port")), settings.resolve_path("server.https.port"));
+    ///     assert_eq!(None, settings.resolve_path("server.ftp.port"));
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `path` -  A string slice that holds the dotted path of the key
+    ///
+    pub fn resolve_path(&self, path: &str) -> Option<(&str, &str)> {
+        let sections = path.rmatch_indices(PATH_SEPARATOR).map(|(index, _)| (&path[..index], &path[index + 1..]));
+        sections.chain(std::iter::once((GLOBAL_SECTION, path))).find_map(|(section_name, key)| {
+            let section = self.get_section(section_name)?;
+            section.get_key_value(key).map(|key_value| (section.name.as_str(), key_value.key.as_str()))
+        })
+    }
+
+    /// Generic method use to get the value of a key addressed by a dotted path
+    /// (see Settings::resolve_path) without a default value
+    /// Returns std::result::Result::Ok(T) or the errors of Settings::get_value, the section of a path
+    /// not resolved is the path without its last name (i.g. SettingsError::SectionNotFound
+    /// of "server.ftp" for "server.ftp.port")
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    ///
+    /// fn main() {
+    ///     let settings: Settings = "[server]\nhttp.port = 80\n".parse().unwrap();
+    ///     assert_eq!(80, settings.get_path_value::<u16>("server.http.port").unwrap());
+    ///     assert!(settings.get_path_value::<u16>("server.ftp.port").is_err());
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `path` -  A string slice that holds the dotted path of the key
+    ///
+    pub fn get_path_value<T: FromStr>(&self, path: &str) -> Result<T, SettingsError> where <T as FromStr>::Err: Debug {
+        let (section_name, key) = match self.resolve_path(path) {
+            Some(resolved) => resolved,
+            None => path.rsplit_once(PATH_SEPARATOR).unwrap_or((GLOBAL_SECTION, path))
+        };
+        self.get_value(section_name, key)
+    }
+
+    // Returns the nodes of all sections and dotted keys, with the last name of the keys:
+    // a section is a node without key, a key is the key of the section path followed by
+    // the names of the key but the last one
+    //  * `self` -  An immutable reference to Setting struct
+    fn tree_entries(&self) -> Vec<(Vec<&str>, Option<&str>)> {
+        let mut entries = vec![];
+        for section in &self.sections {
+            let section_path = self.section_path(section);
+            entries.push((section_path.clone(), None));
+            for key_value in &section.values {
+                let mut segments = section_path.clone();
+                segments.extend(key_value.key.split(PATH_SEPARATOR));
+                let key = segments.pop();
+                entries.push((segments, key));
+            }
+        }
+        entries
+    }
+
+    // Returns the children of a node as paths of names, in the order they are found
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `path` -  A string slice that holds the path of the node
+    fn child_nodes(&self, path: &str) -> Vec<Vec<&str>> {
+        let node = path_segments(path);
+        let mut children: Vec<Vec<&str>> = vec![];
+        for (segments, _) in self.tree_entries() {
+            if segments.len() > node.len() && self.is_path_prefix(&node, &segments) {
+                let child = &segments[..=node.len()];
+                if !children.iter().any(|found| self.name_matching.matches(found[node.len()], child[node.len()])) {
+                    children.push(child.to_vec());
+                }
+            }
+        }
+        children
+    }
+
+    // Returns the path of a section, no names for the GLOBAL section
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section` -  The Section
+    fn section_path<'s>(&self, section: &'s Section) -> Vec<&'s str> {
+        if self.name_matching.matches(&section.name, GLOBAL_SECTION) {
+            vec![]
+        } else {
+            section.name.split(PATH_SEPARATOR).collect()
+        }
+    }
+
+    // Returns true if a path starts with the names of another one,
+    // names are compared as section and key names (see Settings::with_name_matching)
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `prefix` -  The names of the first path
+    //  * `segments` -  The names of the second path
+    fn is_path_prefix(&self, prefix: &[&str], segments: &[&str]) -> bool {
+        prefix.len() <= segments.len() && prefix.iter().zip(segments).all(|(name, other)| self.name_matching.matches(name, other))
+    }
+}
+
+// Returns the names of a dotted path, none for an empty path
+//  * `path` -  A string slice that holds the dotted path
+fn path_segments(path: &str) -> Vec<&str> {
+    if path.is_empty() {
+        vec![]
+    } else {
+        path.split(PATH_SEPARATOR).collect()
+    }
+}
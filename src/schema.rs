@@ -0,0 +1,875 @@
+use std::fmt::Debug;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::document::encode_value;
+use crate::{DropPolicy, NameMatching, Settings, SettingsError, COMMENT_TAG, END_SECTION_TAG, START_SECTION_TAG, INVALID_SCHEMA_MESSAGE_IDX, PATTERN_MISMATCH_MESSAGE_IDX, SCHEMA_VIOLATIONS_MESSAGE_IDX,
+    SETTINGS_MESSAGES, UNKNOWN_KEY_MESSAGE_IDX, UNKNOWN_SECTION_MESSAGE_IDX, VALUE_NOT_ALLOWED_MESSAGE_IDX,
+    VALUE_OUT_OF_RANGE_MESSAGE_IDX};
+
+// Module validating Settings against a schema (schema feature): the schema declares
+// sections and keys with their type, required flag, default value, range, pattern
+// and allowed values; it is built in Rust or loaded from a sidecar settings file
+// where every key "name.attribute = value" sets an attribute of the key 'name'
+// (i.g. "port.type = u16", "port.min = 1024").
+// A schema also generates the commented template of its settings file.
+
+// Separator of key name and attribute in a schema file
+const ATTRIBUTE_SEPARATOR: char = '.';
+// Separator of the allowed values in a schema file
+const VALUES_SEPARATOR: &str = ",";
+// Key of a schema file section describing the section
+const DESCRIPTION_KEY: &str = "description";
+
+/// Enumeration of the types of the values declared by a schema,
+/// a value has a type if it can be parsed with the FromStr trait of the type;
+/// in a schema file they are written in lower case (i.g. "u32", "string")
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueType {
+    #[default]
+    String,
+    Bool,
+    Char,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64
+}
+
+// Names of the value types in a schema file
+const VALUE_TYPE_NAMES: [(ValueType, &str); 17] = [
+    (ValueType::String, "string"),
+    (ValueType::Bool, "bool"),
+    (ValueType::Char, "char"),
+    (ValueType::I8, "i8"),
+    (ValueType::I16, "i16"),
+    (ValueType::I32, "i32"),
+    (ValueType::I64, "i64"),
+    (ValueType::I128, "i128"),
+    (ValueType::Isize, "isize"),
+    (ValueType::U8, "u8"),
+    (ValueType::U16, "u16"),
+    (ValueType::U32, "u32"),
+    (ValueType::U64, "u64"),
+    (ValueType::U128, "u128"),
+    (ValueType::Usize, "usize"),
+    (ValueType::F32, "f32"),
+    (ValueType::F64, "f64")
+];
+
+impl ValueType {
+    /// Returns the name of the type in a schema file (i.g. "u32")
+    ///  * `self` -  An immutable reference to ValueType enumeration
+    ///
+    pub fn name(&self) -> &'static str {
+        VALUE_TYPE_NAMES.iter().find(|(value_type, _)| value_type == self).map(|(_, name)| *name).unwrap_or_default()
+    }
+
+    // Associated function returning the type named in a schema file, None if unknown
+    //  * `name` -  A string slice that holds the lower case type name
+    fn from_name(name: &str) -> Option<Self> {
+        VALUE_TYPE_NAMES.iter().find(|(_, type_name)| *type_name == name).map(|(value_type, _)| *value_type)
+    }
+
+    // Returns the debug representation of the error parsing a value, None if the value has the type
+    //  * `value` -  A string slice that holds the value
+    fn parsing_error(&self, value: &str) -> Option<String> {
+        match self {
+            ValueType::String => None,
+            ValueType::Bool => parsing_error::<bool>(value),
+            ValueType::Char => parsing_error::<char>(value),
+            ValueType::I8 => parsing_error::<i8>(value),
+            ValueType::I16 => parsing_error::<i16>(value),
+            ValueType::I32 => parsing_error::<i32>(value),
+            ValueType::I64 => parsing_error::<i64>(value),
+            ValueType::I128 => parsing_error::<i128>(value),
+            ValueType::Isize => parsing_error::<isize>(value),
+            ValueType::U8 => parsing_error::<u8>(value),
+            ValueType::U16 => parsing_error::<u16>(value),
+            ValueType::U32 => parsing_error::<u32>(value),
+            ValueType::U64 => parsing_error::<u64>(value),
+            ValueType::U128 => parsing_error::<u128>(value),
+            ValueType::Usize => parsing_error::<usize>(value),
+            ValueType::F32 => parsing_error::<f32>(value),
+            ValueType::F64 => parsing_error::<f64>(value)
+        }
+    }
+
+    // Returns true for the numeric types, the ones checked against a range
+    fn is_numeric(&self) -> bool {
+        !matches!(self, ValueType::String | ValueType::Bool | ValueType::Char)
+    }
+}
+
+// Returns the debug representation of the error parsing a value as T
+//  * `value` -  A string slice that holds the value
+fn parsing_error<T: FromStr>(value: &str) -> Option<String> where <T as FromStr>::Err: Debug {
+    value.parse::<T>().err().map(|error| format!("{:#?}", error))
+}
+
+/// Declaration of a key of a section schema
+/// # Examples
+/// ```
+/// use rssettings::{KeySchema, ValueType};
+///
+/// fn main() {
+///     let key = KeySchema::new("level", ValueType::U8).with_required(true).with_min(1.0).with_max(5.0);
+///     let mode = KeySchema::new("mode", ValueType::String).with_values(&["fast", "safe"]).with_default("safe");
+///     let host = KeySchema::new("host", ValueType::String).with_pattern("^[a-z.]+$").unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeySchema {
+    key: String,
+    value_type: ValueType,
+    required: bool,
+    default_value: Option<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+    pattern: Option<Regex>,
+    values: Vec<String>,
+    description: Option<String>,
+}
+
+impl KeySchema {
+    /// Associated function to create the declaration of an optional key
+    ///  * `key` -  A string slice that holds the name of the key
+    ///  * `value_type` -  The type of the key value
+    ///
+    pub fn new(key: &str, value_type: ValueType) -> Self {
+        Self {
+            key: key.to_string(),
+            value_type,
+            required: false,
+            default_value: None,
+            min: None,
+            max: None,
+            pattern: None,
+            values: vec![],
+            description: None
+        }
+    }
+
+    /// Sets if the key is required, a required key with a default value can be missing
+    /// Returns the KeySchema with the new setting
+    ///  * `self` -  The KeySchema struct
+    ///  * `required` -  If true Settings::validate reports the key when missing
+    ///
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets the value of the key when it is missing, see Schema::default_value
+    /// Returns the KeySchema with the new setting
+    ///  * `self` -  The KeySchema struct
+    ///  * `default_value` -  A string slice that holds the default value
+    ///
+    pub fn with_default(mut self, default_value: &str) -> Self {
+        self.default_value = Some(default_value.to_string());
+        self
+    }
+
+    /// Sets the minimum of a numeric value
+    /// Returns the KeySchema with the new setting
+    ///  * `self` -  The KeySchema struct
+    ///  * `min` -  The minimum value
+    ///
+    pub fn with_min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Sets the maximum of a numeric value
+    /// Returns the KeySchema with the new setting
+    ///  * `self` -  The KeySchema struct
+    ///  * `max` -  The maximum value
+    ///
+    pub fn with_max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Sets the regular expression the value has to match, anchor it with ^ and $
+    /// to match the whole value
+    /// Returns the KeySchema with the new setting or std::result::Result::Err(SettingsError::InvalidSchema)
+    /// if the regular expression is not valid
+    ///  * `self` -  The KeySchema struct
+    ///  * `pattern` -  A string slice that holds the regular expression
+    ///
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self, SettingsError> {
+        match Regex::new(pattern) {
+            Ok(regex) => {
+                self.pattern = Some(regex);
+                Ok(self)
+            },
+            Err(error) => {
+                let reason = error.to_string();
+                let message = SETTINGS_MESSAGES[INVALID_SCHEMA_MESSAGE_IDX].replacen("{}", &self.key, 1)
+                    .replacen("{}", "0", 1).replacen("{}", &reason, 1);
+                Err(SettingsError::InvalidSchema { path: self.key.clone(), line: 0, reason, message })
+            }
+        }
+    }
+
+    /// Sets the values allowed for the key
+    /// Returns the KeySchema with the new setting
+    ///  * `self` -  The KeySchema struct
+    ///  * `values` -  The allowed values, any value if empty
+    ///
+    pub fn with_values(mut self, values: &[&str]) -> Self {
+        self.values = values.iter().map(|value| value.to_string()).collect();
+        self
+    }
+
+    /// Sets the description of the key, written as comment lines in the settings file
+    /// template (see Settings::schema_template)
+    /// Returns the KeySchema with the new setting
+    ///  * `self` -  The KeySchema struct
+    ///  * `description` -  A string slice that holds the description, it can have more lines
+    ///
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Returns the name of the key
+    ///  * `self` -  An immutable reference to KeySchema struct
+    ///
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the description of the key, None if it has no description
+    ///  * `self` -  An immutable reference to KeySchema struct
+    ///
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the type of the key value
+    ///  * `self` -  An immutable reference to KeySchema struct
+    ///
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Returns if the key is required
+    ///  * `self` -  An immutable reference to KeySchema struct
+    ///
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns the value of the key when it is missing, None if it has no default value
+    ///  * `self` -  An immutable reference to KeySchema struct
+    ///
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
+
+    // Returns the range text used in errors, i.g. "1..=5", "1..", "..=5"
+    fn range_text(&self) -> String {
+        let min = self.min.map(|min| min.to_string()).unwrap_or_default();
+        let max = self.max.map(|max| format!("={}", max)).unwrap_or_default();
+        format!("{}..{}", min, max)
+    }
+
+    // Returns the comment line listing type and constraints of the key in the template,
+    // i.g. "type: u16, required, range: 1024.., values: fast,safe, pattern: ^[a-z]+$"
+    fn constraints_text(&self) -> String {
+        let mut constraints = vec![format!("type: {}", self.value_type.name())];
+        if self.required {
+            constraints.push(String::from("required"));
+        }
+        if self.min.is_some() || self.max.is_some() {
+            constraints.push(format!("range: {}", self.range_text()));
+        }
+        if !self.values.is_empty() {
+            constraints.push(format!("values: {}", self.values.join(VALUES_SEPARATOR)));
+        }
+        if let Some(pattern) = &self.pattern {
+            constraints.push(format!("pattern: {}", pattern.as_str()));
+        }
+        constraints.join(", ")
+    }
+}
+
+/// Declaration of a section of a schema and of its keys
+#[derive(Debug, Clone)]
+pub struct SectionSchema {
+    name: String,
+    keys: Vec<KeySchema>,
+    description: Option<String>,
+    name_matching: NameMatching,
+}
+
+impl SectionSchema {
+    /// Associated function to create the declaration of a section without keys
+    ///  * `name` -  A string slice that holds the name of the section
+    ///
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), keys: vec![], description: None, name_matching: NameMatching::default() }
+    }
+
+    /// Adds the declaration of a key, a key with the same name is replaced,
+    /// names are compared as the Schema holding the section compares them
+    /// (see Schema::with_name_matching)
+    /// Returns the SectionSchema with the new key
+    ///  * `self` -  The SectionSchema struct
+    ///  * `key` -  The key declaration
+    ///
+    pub fn with_key(mut self, key: KeySchema) -> Self {
+        self.add_key(key);
+        self
+    }
+
+    /// Sets the description of the section, written as comment lines in the settings file
+    /// template (see Settings::schema_template)
+    /// Returns the SectionSchema with the new setting
+    ///  * `self` -  The SectionSchema struct
+    ///  * `description` -  A string slice that holds the description, it can have more lines
+    ///
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Returns the name of the section
+    ///  * `self` -  An immutable reference to SectionSchema struct
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the description of the section, None if it has no description
+    ///  * `self` -  An immutable reference to SectionSchema struct
+    ///
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the declaration of a key, None if the key is not declared
+    ///  * `self` -  An immutable reference to SectionSchema struct
+    ///  * `key` -  A string slice that holds the name of the key
+    ///
+    pub fn key(&self, key: &str) -> Option<&KeySchema> {
+        self.keys.iter().find(|key_schema| self.name_matching.matches(&key_schema.key, key))
+    }
+
+    // Adds the declaration of a key replacing a key with the same name
+    fn add_key(&mut self, key: KeySchema) {
+        let name_matching = self.name_matching;
+        match self.keys.iter_mut().find(|existing| name_matching.matches(&existing.key, &key.key)) {
+            Some(existing) => *existing = key,
+            None => self.keys.push(key)
+        }
+    }
+
+    // Sets how key names are compared, the keys that become the same key
+    // are replaced by the last declared one
+    fn set_name_matching(&mut self, name_matching: NameMatching) {
+        self.name_matching = name_matching;
+        for key in std::mem::take(&mut self.keys) {
+            self.add_key(key);
+        }
+    }
+}
+
+/// Schema of a settings file checked by Settings::validate, available with the schema feature
+/// It can be built in Rust or loaded from a schema file (see Schema::load) where the sections
+/// are the declared sections and every key "name.attribute = value" sets an attribute of the key
+/// 'name': 'type' (string, bool, char, i8 ... i128, isize, u8 ... u128, usize, f32, f64; string
+/// if missing), 'required' (true or false), 'default', 'min', 'max', 'pattern' (a regular expression),
+/// 'values' (the allowed values separated by ',') and 'description'; the key "description"
+/// describes the section
+/// # Examples
+/// ```
+/// use rssettings::{KeySchema, Schema, SectionSchema, Settings, ValueType};
+///
+/// fn main() {
+///     let schema = Schema::new().with_section(SectionSchema::new("GLOBAL")
+///         .with_key(KeySchema::new("u32_value", ValueType::U32).with_required(true).with_max(1000.0))
+///         .with_key(KeySchema::new("bool_value", ValueType::Bool)));
+///     let from_file: Schema = "[GLOBAL]\nu32_value.type = u32\nu32_value.required = true\n".parse().unwrap();
+///     let mut settings = Settings::new();
+///     settings.load("test_files/settings.ini").unwrap();
+///     assert!(settings.validate(&schema).is_ok());
+///     assert!(settings.validate(&from_file).is_ok());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    sections: Vec<SectionSchema>,
+    unknown_keys: bool,
+    name_matching: NameMatching,
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Schema {
+    /// Associated function to create a schema without sections,
+    /// keys and sections not declared are allowed
+    ///
+    pub fn new() -> Self {
+        Self { sections: vec![], unknown_keys: true, name_matching: NameMatching::default() }
+    }
+
+    /// Adds the declaration of a section, a section with the same name is replaced
+    /// Returns the Schema with the new section
+    ///  * `self` -  The Schema struct
+    ///  * `section` -  The section declaration
+    ///
+    pub fn with_section(mut self, section: SectionSchema) -> Self {
+        self.add_section(section);
+        self
+    }
+
+    /// Sets how section and key names are compared by Schema::section, Schema::default_value,
+    /// SectionSchema::key and when declarations with the same name are replaced,
+    /// the default is NameMatching::Exact; Settings::validate compares names as the
+    /// validated Settings does (see Settings::with_name_matching), so it should be the same
+    /// Declarations that become the same section or key are replaced by the last one
+    /// Returns the Schema with the new setting
+    /// # Examples
+    /// ```
+    /// use rssettings::{KeySchema, NameMatching, Schema, SectionSchema, ValueType};
+    ///
+    /// fn main() {
+    ///     let schema = Schema::new().with_name_matching(NameMatching::AsciiCaseInsensitive)
+    ///         .with_section(SectionSchema::new("LOG").with_key(KeySchema::new("level", ValueType::U8).with_default("3")));
+    ///     assert_eq!(Some("3"), schema.default_value("log", "Level"));
+    /// }
+    /// ```
+    ///  * `self` -  The Schema struct
+    ///  * `name_matching` -  How section and key names are compared
+    ///
+    pub fn with_name_matching(mut self, name_matching: NameMatching) -> Self {
+        self.name_matching = name_matching;
+        for section in std::mem::take(&mut self.sections) {
+            self.add_section(section);
+        }
+        self
+    }
+
+    /// Returns how section and key names are compared
+    ///  * `self` -  An immutable reference to Schema struct
+    ///
+    pub fn name_matching(&self) -> NameMatching {
+        self.name_matching
+    }
+
+    /// Sets if sections and keys not declared are allowed, they are by default
+    /// Returns the Schema with the new setting
+    ///  * `self` -  The Schema struct
+    ///  * `unknown_keys` -  If false Settings::validate reports sections and keys not declared
+    ///
+    pub fn with_unknown_keys(mut self, unknown_keys: bool) -> Self {
+        self.unknown_keys = unknown_keys;
+        self
+    }
+
+    /// Returns if sections and keys not declared are allowed
+    ///  * `self` -  An immutable reference to Schema struct
+    ///
+    pub fn unknown_keys(&self) -> bool {
+        self.unknown_keys
+    }
+
+    /// Returns the declaration of a section, None if the section is not declared
+    ///  * `self` -  An immutable reference to Schema struct
+    ///  * `section_name` -  A string slice that holds the name of the section
+    ///
+    pub fn section(&self, section_name: &str) -> Option<&SectionSchema> {
+        self.sections.iter().find(|section| self.name_matching.matches(&section.name, section_name))
+    }
+
+    // Adds the declaration of a section replacing a section with the same name,
+    // its keys are compared as the schema compares names
+    fn add_section(&mut self, mut section: SectionSchema) {
+        section.set_name_matching(self.name_matching);
+        let name_matching = self.name_matching;
+        match self.sections.iter_mut().find(|existing| name_matching.matches(&existing.name, &section.name)) {
+            Some(existing) => *existing = section,
+            None => self.sections.push(section)
+        }
+    }
+
+    /// Returns the default value of a key, None if the key is not declared or has no default
+    ///  * `self` -  An immutable reference to Schema struct
+    ///  * `section_name` -  A string slice that holds the name of the section
+    ///  * `key` -  A string slice that holds the name of the key
+    ///
+    pub fn default_value(&self, section_name: &str, key: &str) -> Option<&str> {
+        self.section(section_name)?.key(key)?.default_value()
+    }
+
+    /// Loads a schema file, see Schema
+    /// Returns std::result::Result::Ok(Schema), the error of Settings::load if the file
+    /// can not be loaded or std::result::Result::Err(SettingsError::InvalidSchema) with the line
+    /// of the first invalid attribute
+    /// # Examples
+    /// ```
+    /// use rssettings::{Schema, Settings};
+    ///
+    /// fn main() {
+    ///     let schema = Schema::load("test_files/settings.schema.ini").unwrap();
+    ///     let mut settings = Settings::new();
+    ///     settings.load("test_files/settings.ini").unwrap();
+    ///     assert!(settings.validate(&schema).is_ok());
+    /// }
+    /// ```
+    ///  * `path` -  A Path as reference to the schema file to load
+    ///
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SettingsError> {
+        let mut settings = Settings::new().with_drop_policy(DropPolicy::Never);
+        settings.load(path)?;
+        Self::from_settings(&settings)
+    }
+
+    // Associated function building the schema declared by a loaded schema file
+    //  * `settings` -  The Settings of the schema file
+    fn from_settings(settings: &Settings) -> Result<Self, SettingsError> {
+        let mut schema = Schema::new();
+        for section in &settings.sections {
+            let mut keys: Vec<KeySchema> = vec![];
+            let mut description = None;
+            for key_value in &section.values {
+                if key_value.key == DESCRIPTION_KEY {
+                    description = Some(key_value.value.clone());
+                    continue;
+                }
+                let invalid = |reason: &str| settings.invalid_schema_error(key_value.line_cnt, reason);
+                let (key, attribute) = match key_value.key.rsplit_once(ATTRIBUTE_SEPARATOR) {
+                    Some((key, attribute)) if !key.is_empty() => (key, attribute),
+                    _ => return Err(invalid("expected 'key.attribute'"))
+                };
+                let index = match keys.iter().position(|key_schema| key_schema.key == key) {
+                    Some(index) => index,
+                    None => {
+                        keys.push(KeySchema::new(key, ValueType::String));
+                        keys.len() - 1
+                    }
+                };
+                let key_schema = keys[index].clone();
+                let value = key_value.value.as_str();
+                keys[index] = match attribute {
+                    "type" => match ValueType::from_name(value) {
+                        Some(value_type) => KeySchema { value_type, ..key_schema },
+                        None => return Err(invalid("unknown type"))
+                    },
+                    "required" => match value.parse::<bool>() {
+                        Ok(required) => key_schema.with_required(required),
+                        Err(_) => return Err(invalid("expected 'true' or 'false'"))
+                    },
+                    "default" => key_schema.with_default(value),
+                    "min" => match value.parse::<f64>() {
+                        Ok(min) => key_schema.with_min(min),
+                        Err(_) => return Err(invalid("expected a number"))
+                    },
+                    "max" => match value.parse::<f64>() {
+                        Ok(max) => key_schema.with_max(max),
+                        Err(_) => return Err(invalid("expected a number"))
+                    },
+                    "pattern" => match key_schema.with_pattern(value) {
+                        Ok(key_schema) => key_schema,
+                        Err(SettingsError::InvalidSchema { reason, .. }) => return Err(invalid(&reason)),
+                        Err(error) => return Err(error)
+                    },
+                    "values" => {
+                        let values: Vec<&str> = value.split(VALUES_SEPARATOR).map(str::trim).collect();
+                        key_schema.with_values(&values)
+                    },
+                    "description" => key_schema.with_description(value),
+                    _ => return Err(invalid("unknown attribute"))
+                };
+            }
+            // a section without keys is declared only if it has been written
+            if !keys.is_empty() || section.line_cnt > 0 {
+                schema.sections.push(SectionSchema { name: section.name.clone(), keys, description, name_matching: NameMatching::default() });
+            }
+        }
+        Ok(schema)
+    }
+}
+
+/// implementation of FromStr trait for the Schema structure,
+/// the string is the content of a schema file (see Schema::load)
+impl FromStr for Schema {
+    type Err = SettingsError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let settings: Settings = text.parse()?;
+        Self::from_settings(&settings)
+    }
+}
+
+impl Settings {
+    /// Associated function returning the text of a settings file template generated
+    /// from a schema (available with the schema feature): every declared section and key
+    /// in declaration order, descriptions, types and constraints as comment lines and
+    /// default values as values; keys without default value are written commented out
+    /// # Examples
+    /// ```
+    /// use rssettings::{KeySchema, Schema, SectionSchema, Settings, ValueType};
+    ///
+    /// fn main() {
+    ///     let schema = Schema::new().with_section(SectionSchema::new("LOG").with_description("Logging")
+    ///         .with_key(KeySchema::new("level", ValueType::U8).with_default("3").with_description("Verbosity")));
+    ///     assert_eq!("# Logging\n[LOG]\n# Verbosity\n# type: u8\nlevel = 3\n", Settings::schema_template(&schema));
+    /// }
+    /// ```
+    ///  * `schema` -  The schema of the settings file
+    ///
+    pub fn schema_template(schema: &Schema) -> String {
+        let mut template = String::new();
+        for (index, section) in schema.sections.iter().enumerate() {
+            if index > 0 {
+                template.push('\n');
+            }
+            Self::push_comment(&mut template, section.description());
+            template.push_str(&format!("{}{}{}\n", START_SECTION_TAG, section.name, END_SECTION_TAG));
+            for key in &section.keys {
+                Self::push_comment(&mut template, key.description());
+                Self::push_comment(&mut template, Some(&key.constraints_text()));
+                match key.default_value() {
+                    Some(default_value) => template.push_str(&format!("{} = {}\n", key.key, encode_value(default_value, None))),
+                    None => template.push_str(&format!("{} {} =\n", COMMENT_TAG, key.key))
+                }
+            }
+        }
+        template
+    }
+
+    /// Loads a settings file, the file is created with the template generated from
+    /// the schema (see Settings::schema_template) if it does not exist
+    /// (available with the schema feature)
+    /// The template is written to a temporary file linked to the settings file only
+    /// when complete, a file created in the meantime by someone else is loaded as it is
+    /// Returns std::result::Result::Ok(()) if the settings file has been loaded,
+    /// std::result::Result::Err(SettingsError::WritingFile) if it can not be created
+    /// or the errors of Settings::load
+    /// # Examples
+    /// ```
+    /// use rssettings::{KeySchema, Schema, SectionSchema, Settings, ValueType};
+    ///
+    /// fn main() {
+    ///     let schema = Schema::new().with_section(SectionSchema::new("LOG")
+    ///         .with_key(KeySchema::new("level", ValueType::U8).with_default("3")));
+    ///     let path = std::env::temp_dir().join("rssettings_load_or_create_example.ini");
+    ///     # let _ = std::fs::remove_file(&path);
+    ///     let mut settings = Settings::new();
+    ///     match settings.load_or_create(&path, &schema) {
+    ///         Result::Ok(()) => assert_eq!(3, settings.get("LOG", "level", 0).value),
+    ///         Result::Err(error) => eprintln!("{}", error)
+    ///     }
+    ///     # let _ = std::fs::remove_file(&path);
+    /// }
+    /// ```
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `path` -  A Path as reference to the settings file to load or create
+    ///  * `schema` -  The schema generating the template
+    ///
+    pub fn load_or_create<P: AsRef<Path>>(&mut self, path: P, schema: &Schema) -> Result<(), SettingsError> {
+        let path = path.as_ref();
+        if !self.is_initialize() {
+            // an existing file, even if created in the meantime by someone else, is not replaced
+            match Self::create_atomically(path, Self::schema_template(schema).as_bytes()) {
+                Ok(()) => {},
+                Err(ioerror) if ioerror.kind() == io::ErrorKind::AlreadyExists => {},
+                Err(ioerror) => return Err(self.writing_file_error(path.to_str().unwrap_or(""), ioerror))
+            }
+        }
+        self.load(path)
+    }
+
+    // Associated function that creates a new file in a crash safe way:
+    // the content is written and synced to a temporary file in the same directory
+    // as Settings::write_atomically does, and then the temporary file is hard linked
+    // to the new file and removed, so an existing file is never replaced and
+    // the new file is either missing or complete
+    // Returns the io error of the first failed operation, io::ErrorKind::AlreadyExists
+    // if the file exists, the temporary file is removed
+    //  * `file` -  The path of the file to create
+    //  * `content` -  The bytes to write
+    fn create_atomically(file: &Path, content: &[u8]) -> io::Result<()> {
+        Self::write_through_temp_file(file, content, false)
+    }
+
+    // Associated function appending a text as comment lines to a template
+    //  * `template` -  The template text
+    //  * `text` -  The comment text, nothing is appended if None
+    fn push_comment(template: &mut String, text: Option<&str>) {
+        for line in text.iter().flat_map(|text| text.lines()) {
+            template.push_str(&format!("{} {}\n", COMMENT_TAG, line));
+        }
+    }
+
+    /// Checks the values of Settings against a schema (available with the schema feature):
+    /// missing required sections and keys, values without the declared type, out of range,
+    /// not matching the pattern or not allowed and, if the schema does not allow them,
+    /// sections and keys not declared. Values are checked as Settings::get returns them
+    /// (interpolated and overridden), every element of an array key (see Settings::with_array_keys)
+    /// is checked against the declaration of the "key[]" key
+    /// Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError::SchemaViolations)
+    /// holding every violation with the settings file line of the value (0 if overridden)
+    /// # Examples
+    /// ```
+    /// use rssettings::{Schema, Settings, SettingsError};
+    ///
+    /// fn main() {
+    ///     let schema: Schema = "[LOG]\nlevel.type = u8\nlevel.max = 5\n".parse().unwrap();
+    ///     let settings: Settings = "[LOG]\nlevel = 9\n".parse().unwrap();
+    ///     match settings.validate(&schema) {
+    ///         Result::Ok(()) => {},
+    ///         Result::Err(SettingsError::SchemaViolations { errors, .. }) => {
+    ///             for error in errors {
+    ///                 eprintln!("{}", error);
+    ///             }
+    ///         },
+    ///         Result::Err(error) => eprintln!("{}", error)
+    ///     }
+    /// }
+    /// ```
+    ///  * `self` -  An immutable reference to Setting struct
+    ///  * `schema` -  The schema to check
+    ///
+    pub fn validate(&self, schema: &Schema) -> Result<(), SettingsError> {
+        let mut errors = vec![];
+        for section_schema in &schema.sections {
+            let section = self.get_section(&section_schema.name);
+            let mut section_reported = false;
+            for key_schema in &section_schema.keys {
+                let values: Vec<(String, usize)> = match self.value_override(&section_schema.name, &key_schema.key) {
+                    Some(value) => vec![(value.to_string(), 0)],
+                    None => section.iter().flat_map(|section| section.values.iter())
+                        .filter(|key_value| self.name_matching.matches(&key_value.key, &key_schema.key))
+                        .map(|key_value| (key_value.value.clone(), key_value.line_cnt))
+                        .collect()
+                };
+                if values.is_empty() {
+                    if key_schema.required && key_schema.default_value.is_none() {
+                        if section.is_some() {
+                            errors.push(self.key_not_found_error(&section_schema.name, &key_schema.key));
+                        } else if !section_reported {
+                            errors.push(self.section_not_found_error(&section_schema.name));
+                            section_reported = true;
+                        }
+                    }
+                    continue;
+                }
+                for (value, line) in values {
+                    let checked = self.interpolate(&section_schema.name, &key_schema.key, line, &value)
+                        .and_then(|value| self.check_value(&section_schema.name, key_schema, &value, line));
+                    if let Err(error) = checked {
+                        errors.push(error);
+                    }
+                }
+            }
+        }
+        if !schema.unknown_keys {
+            self.check_unknown_keys(schema, &mut errors);
+        }
+        if errors.is_empty() {
+            return Ok(());
+        }
+        let path = self.path.clone();
+        let messages = errors.iter().map(|error| self.message_with_line(error)).collect::<Vec<String>>().join("; ");
+        let message = self.format_message(SCHEMA_VIOLATIONS_MESSAGE_IDX, vec![&path, &messages]);
+        Err(SettingsError::SchemaViolations { path, errors, message })
+    }
+
+    // Checks a value against the declaration of its key
+    // Returns the first violation found
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `section_name` -  A string slice that holds the name of the Section
+    //  * `key_schema` -  The key declaration
+    //  * `value` -  A string slice that holds the interpolated value
+    //  * `line` -  The settings file line of the value
+    fn check_value(&self, section_name: &str, key_schema: &KeySchema, value: &str, line: usize) -> Result<(), SettingsError> {
+        let section = section_name.to_string();
+        let key = key_schema.key.clone();
+        if let Some(error) = key_schema.value_type.parsing_error(value) {
+            return Err(self.parsing_error(section_name, &key_schema.key, line, error));
+        }
+        let value = value.to_string();
+        if !key_schema.values.is_empty() && !key_schema.values.contains(&value) {
+            let values = key_schema.values.join(VALUES_SEPARATOR);
+            let message = self.format_message(VALUE_NOT_ALLOWED_MESSAGE_IDX, vec![&section, &key, &value, &values]);
+            return Err(SettingsError::ValueNotAllowed { section, key, line, value, message });
+        }
+        if key_schema.value_type.is_numeric() && (key_schema.min.is_some() || key_schema.max.is_some()) {
+            let number = value.parse::<f64>().unwrap_or(f64::NAN);
+            let too_small = key_schema.min.is_some_and(|min| number < min);
+            let too_big = key_schema.max.is_some_and(|max| number > max);
+            if too_small || too_big {
+                let range = key_schema.range_text();
+                let message = self.format_message(VALUE_OUT_OF_RANGE_MESSAGE_IDX, vec![&section, &key, &value, &range]);
+                return Err(SettingsError::ValueOutOfRange { section, key, line, value, message });
+            }
+        }
+        if let Some(pattern) = key_schema.pattern.as_ref().filter(|pattern| !pattern.is_match(&value)) {
+            let pattern = pattern.as_str().to_string();
+            let message = self.format_message(PATTERN_MISMATCH_MESSAGE_IDX, vec![&section, &key, &value, &pattern]);
+            return Err(SettingsError::PatternMismatch { section, key, line, value, message });
+        }
+        Ok(())
+    }
+
+    // Adds to the violations the sections and keys not declared by the schema,
+    // sections without keys and never written (i.g. an empty GLOBAL) are skipped
+    //  * `self` -  An immutable reference to Setting struct
+    //  * `schema` -  The schema to check
+    //  * `errors` -  The violations found
+    fn check_unknown_keys(&self, schema: &Schema, errors: &mut Vec<SettingsError>) {
+        for section in &self.sections {
+            let section_schema = schema.sections.iter().find(|section_schema| self.name_matching.matches(&section_schema.name, &section.name));
+            let section_schema = match section_schema {
+                Some(section_schema) => section_schema,
+                None => {
+                    if !section.values.is_empty() || section.line_cnt > 0 {
+                        let name = section.name.clone();
+                        let message = self.format_message(UNKNOWN_SECTION_MESSAGE_IDX, vec![&name]);
+                        errors.push(SettingsError::UnknownSection { section: name, line: section.line_cnt, message });
+                    }
+                    continue;
+                }
+            };
+            let declared = |key: &str| section_schema.keys.iter().any(|key_schema| self.name_matching.matches(&key_schema.key, key));
+            for key_value in section.values.iter().filter(|key_value| !declared(&key_value.key)) {
+                let name = section.name.clone();
+                let key = key_value.key.clone();
+                let message = self.format_message(UNKNOWN_KEY_MESSAGE_IDX, vec![&name, &key]);
+                errors.push(SettingsError::UnknownKey { section: name, key, line: key_value.line_cnt, message });
+            }
+        }
+    }
+
+    // Returns the error of an invalid attribute of a schema file
+    //  * `self` -  An immutable reference to the Setting struct of the schema file
+    //  * `line` -  The line of the attribute
+    //  * `reason` -  A string slice that describes the problem
+    fn invalid_schema_error(&self, line: usize, reason: &str) -> SettingsError {
+        let path = self.path.clone();
+        let reason = reason.to_string();
+        let message = self.format_message(INVALID_SCHEMA_MESSAGE_IDX, vec![&path, &line.to_string(), &reason]);
+        SettingsError::InvalidSchema { path, line, reason, message }
+    }
+}
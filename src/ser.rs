@@ -0,0 +1,375 @@
+use std::fmt::Display;
+
+use serde::ser::{self, Impossible, Serialize, SerializeMap, SerializeStruct};
+
+use crate::{Settings, SettingsError};
+
+// Module implementing serde::Serializer for Settings (serde feature):
+// the fields of a struct are written as sections and the fields of the
+// sections as keys, values are written with their Display implementation
+// through Settings::set_or_insert.
+
+impl Settings {
+    /// Serializes a struct in Settings, every field of the struct is a section
+    /// and must be a struct (or a map) whose fields are the keys, written with
+    /// Settings::set_or_insert: missing sections and keys are inserted,
+    /// None values are not written (available with the serde feature)
+    /// Returns std::result::Result::Ok(()) or std::result::Result::Err(SettingsError)
+    /// if a section or key name is not valid or a value can not be written in a settings file
+    /// # Examples
+    /// ```
+    /// use rssettings::Settings;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Log {
+    ///     enabled: bool,
+    ///     level: u8,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// #[allow(non_snake_case)]
+    /// struct AppConfig {
+    ///     LOG: Log,
+    /// }
+    ///
+    /// fn main() {
+    ///     let mut settings = Settings::new();
+    ///     let config = AppConfig { LOG: Log { enabled: true, level: 3 } };
+    ///     if let Result::Err(error) = settings.serialize(&config) {
+    ///         eprintln!("{}", error);
+    ///     }
+    ///     assert_eq!(3, settings.get("LOG", "level", 0).value);
+    /// }
+    /// ```
+    ///  * `self` -  A mutable reference to Setting struct
+    ///  * `value` -  The serialized struct
+    ///
+    pub fn serialize<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SettingsError> {
+        value.serialize(&mut *self).map_err(|error| match error {
+            // errors created by serde have english messages
+            SettingsError::Serde { source, .. } => self.serde_error(source),
+            error => error
+        })
+    }
+}
+
+// serde::ser::Error trait implementation for SettingsError enumeration,
+// the message uses the default english messages table
+impl ser::Error for SettingsError {
+    fn custom<T: Display>(msg: T) -> Self {
+        <SettingsError as serde::de::Error>::custom(msg)
+    }
+}
+
+// Returns the error of a value that can not be serialized in Settings
+fn unsupported(what: &str) -> SettingsError {
+    <SettingsError as ser::Error>::custom(format!("{} can not be serialized in settings", what))
+}
+
+// Generates the serialize methods of the types refused by a serializer
+macro_rules! serialize_unsupported {
+    ($what:expr; $($method:ident($($arg:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok, SettingsError> {
+                Err(unsupported($what))
+            }
+        )*
+    };
+}
+
+// Generates the serialize methods of the compound types refused by a serializer
+macro_rules! serialize_compound_unsupported {
+    ($what:expr) => {
+        fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, SettingsError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, SettingsError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, SettingsError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, SettingsError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, SettingsError> {
+            Err(unsupported($what))
+        }
+
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<Self::Ok, SettingsError> {
+            Err(unsupported($what))
+        }
+    };
+}
+
+// Generates the serialize methods of the scalar types refused by a serializer
+macro_rules! serialize_scalars_unsupported {
+    ($what:expr) => {
+        serialize_unsupported! { $what;
+            serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32),
+            serialize_i64(i64), serialize_i128(i128), serialize_u8(u8), serialize_u16(u16),
+            serialize_u32(u32), serialize_u64(u64), serialize_u128(u128), serialize_f32(f32),
+            serialize_f64(f64), serialize_char(char), serialize_str(&str), serialize_bytes(&[u8]),
+            serialize_unit_struct(&'static str),
+            serialize_unit_variant(&'static str, u32, &'static str),
+        }
+    };
+}
+
+const SETTINGS_UNSUPPORTED: &str = "only a struct or a map of sections";
+const SECTION_UNSUPPORTED: &str = "only a struct or a map of keys as section";
+const VALUE_UNSUPPORTED: &str = "only a scalar as value";
+
+// Serializer of the whole Settings, a struct or a map of sections
+impl<'a> ser::Serializer for &'a mut Settings {
+    type Ok = ();
+    type Error = SettingsError;
+    type SerializeSeq = Impossible<(), SettingsError>;
+    type SerializeTuple = Impossible<(), SettingsError>;
+    type SerializeTupleStruct = Impossible<(), SettingsError>;
+    type SerializeTupleVariant = Impossible<(), SettingsError>;
+    type SerializeMap = SectionsSerializer<'a>;
+    type SerializeStruct = SectionsSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), SettingsError>;
+
+    serialize_scalars_unsupported!(SETTINGS_UNSUPPORTED);
+    serialize_compound_unsupported!(SETTINGS_UNSUPPORTED);
+
+    fn serialize_none(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SettingsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<(), SettingsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<SectionsSerializer<'a>, SettingsError> {
+        Ok(SectionsSerializer { settings: self, section: None })
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<SectionsSerializer<'a>, SettingsError> {
+        Ok(SectionsSerializer { settings: self, section: None })
+    }
+}
+
+// Serializer of the sections, 'section' is the name of the map entry being serialized
+pub struct SectionsSerializer<'a> {
+    settings: &'a mut Settings,
+    section: Option<String>,
+}
+
+impl<'a> SerializeStruct for SectionsSerializer<'a> {
+    type Ok = ();
+    type Error = SettingsError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, name: &'static str, value: &T) -> Result<(), SettingsError> {
+        value.serialize(SectionSerializer { settings: self.settings, section: name.to_string() })
+    }
+
+    fn end(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeMap for SectionsSerializer<'a> {
+    type Ok = ();
+    type Error = SettingsError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SettingsError> {
+        self.section = key.serialize(ValueSerializer)?;
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SettingsError> {
+        match self.section.take() {
+            Some(section) => value.serialize(SectionSerializer { settings: self.settings, section }),
+            None => Err(unsupported("a section without name"))
+        }
+    }
+
+    fn end(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+}
+
+// Serializer of a section, a struct or a map of keys
+struct SectionSerializer<'a> {
+    settings: &'a mut Settings,
+    section: String,
+}
+
+impl<'a> ser::Serializer for SectionSerializer<'a> {
+    type Ok = ();
+    type Error = SettingsError;
+    type SerializeSeq = Impossible<(), SettingsError>;
+    type SerializeTuple = Impossible<(), SettingsError>;
+    type SerializeTupleStruct = Impossible<(), SettingsError>;
+    type SerializeTupleVariant = Impossible<(), SettingsError>;
+    type SerializeMap = KeysSerializer<'a>;
+    type SerializeStruct = KeysSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), SettingsError>;
+
+    serialize_scalars_unsupported!(SECTION_UNSUPPORTED);
+    serialize_compound_unsupported!(SECTION_UNSUPPORTED);
+
+    fn serialize_none(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SettingsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<(), SettingsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<KeysSerializer<'a>, SettingsError> {
+        self.settings.add_section(&self.section)?;
+        Ok(KeysSerializer { settings: self.settings, section: self.section, key: None })
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<KeysSerializer<'a>, SettingsError> {
+        self.settings.add_section(&self.section)?;
+        Ok(KeysSerializer { settings: self.settings, section: self.section, key: None })
+    }
+}
+
+// Serializer of the keys of a section, 'key' is the name of the map entry being serialized
+pub struct KeysSerializer<'a> {
+    settings: &'a mut Settings,
+    section: String,
+    key: Option<String>,
+}
+
+impl<'a> KeysSerializer<'a> {
+    // Writes a value in Settings, None values are not written
+    fn set<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), SettingsError> {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.settings.set_or_insert(&self.section, key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> SerializeStruct for KeysSerializer<'a> {
+    type Ok = ();
+    type Error = SettingsError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, name: &'static str, value: &T) -> Result<(), SettingsError> {
+        self.set(name, value)
+    }
+
+    fn end(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeMap for KeysSerializer<'a> {
+    type Ok = ();
+    type Error = SettingsError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SettingsError> {
+        self.key = key.serialize(ValueSerializer)?;
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SettingsError> {
+        match self.key.take() {
+            Some(key) => self.set(&key, value),
+            None => Err(unsupported("a key without name"))
+        }
+    }
+
+    fn end(self) -> Result<(), SettingsError> {
+        Ok(())
+    }
+}
+
+// Serializer of a single value, written with the Display trait,
+// Ok(None) means that the value must not be written
+struct ValueSerializer;
+
+// Generates the serialize methods of the types written with Display
+macro_rules! serialize_displayed {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Option<String>, SettingsError> {
+                Ok(Some(value.to_string()))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Option<String>;
+    type Error = SettingsError;
+    type SerializeSeq = Impossible<Option<String>, SettingsError>;
+    type SerializeTuple = Impossible<Option<String>, SettingsError>;
+    type SerializeTupleStruct = Impossible<Option<String>, SettingsError>;
+    type SerializeTupleVariant = Impossible<Option<String>, SettingsError>;
+    type SerializeMap = Impossible<Option<String>, SettingsError>;
+    type SerializeStruct = Impossible<Option<String>, SettingsError>;
+    type SerializeStructVariant = Impossible<Option<String>, SettingsError>;
+
+    serialize_displayed! {
+        serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32),
+        serialize_i64(i64), serialize_i128(i128), serialize_u8(u8), serialize_u16(u16),
+        serialize_u32(u32), serialize_u64(u64), serialize_u128(u128), serialize_f32(f32),
+        serialize_f64(f64), serialize_char(char), serialize_str(&str),
+    }
+    serialize_unsupported! { VALUE_UNSUPPORTED;
+        serialize_bytes(&[u8]),
+    }
+    serialize_compound_unsupported!(VALUE_UNSUPPORTED);
+
+    fn serialize_none(self) -> Result<Option<String>, SettingsError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<String>, SettingsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<String>, SettingsError> {
+        Ok(Some(String::new()))
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Option<String>, SettingsError> {
+        Ok(Some(String::new()))
+    }
+
+    // unit variants are written with their name
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<Option<String>, SettingsError> {
+        Ok(Some(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<Option<String>, SettingsError> {
+        value.serialize(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, SettingsError> {
+        Err(unsupported(VALUE_UNSUPPORTED))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, SettingsError> {
+        Err(unsupported(VALUE_UNSUPPORTED))
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use crate::{Settings, SettingsError};
+
+// Module watching the settings file of a shared Settings: the file is polled,
+// when it is modified Settings is reloaded and the registered callbacks
+// receive the list of changed key/value pairs.
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A key/value pair changed by the reload of a watched settings file
+/// 'section' and 'key' identify the key/value pair
+/// 'old_value' is the value before the reload, None if the key has been added
+/// 'new_value' is the value after the reload, None if the key has been removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    pub section: String,
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+// Boxed callbacks registered by SettingsWatcher::on_change and SettingsWatcher::on_error
+type ChangeCallback = Box<dyn FnMut(&[SettingChange]) + Send>;
+type ErrorCallback = Box<dyn FnMut(&SettingsError) + Send>;
+
+/// Watcher of the settings file of a Settings shared between threads
+/// It polls the modification time and the content of the settings file and,
+/// when it has been modified by someone else, reloads Settings (see Settings::reload)
+/// and calls the change callbacks with the changed key/value pairs.
+/// If the modified file can not be loaded Settings keeps its previous values
+/// and the error callbacks receive the error, the same file is not loaded again
+/// until it is modified again.
+/// Changes made to Settings and not yet saved are not discarded by the reload:
+/// they are applied again to the reloaded file as Settings::save does with
+/// ConflictPolicy::Merge, so the modified file is reported by the change callbacks
+/// and the unsaved values are kept. If a change can not be applied again
+/// Settings keeps its previous values and the error callbacks receive a
+/// SettingsError::Conflict error.
+/// Elements of array keys (see Settings::with_array_keys) are compared by position.
+/// # Examples
+/// ```
+/// use rssettings::{Settings, SettingsWatcher};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let mut settings = Settings::new();
+///     if let Result::Ok(()) = settings.load("test_files/settings.ini") {
+///         let settings = Arc::new(Mutex::new(settings));
+///         let watcher = SettingsWatcher::new(Arc::clone(&settings))
+///             .with_interval(Duration::from_millis(500))
+///             .on_change(|changes| {
+///                 for change in changes {
+///                     println!("[{}] {}: {:?} -> {:?}", change.section, change.key, change.old_value, change.new_value);
+///                 }
+///             })
+///             .on_error(|error| eprintln!("{}", error))
+///             .start();
+///         // ...
+///         watcher.stop();
+///     }
+/// }
+/// ```
+pub struct SettingsWatcher {
+    settings: Arc<Mutex<Settings>>,
+    interval: Duration,
+    change_callbacks: Vec<ChangeCallback>,
+    error_callbacks: Vec<ErrorCallback>,
+    failed_file: Option<(Option<SystemTime>, u64)>,
+}
+
+impl SettingsWatcher {
+    /// Associated function to create a watcher of the settings file of a shared Settings
+    /// the file is polled every second, see SettingsWatcher::with_interval
+    ///  * `settings` -  The Settings shared with the watcher
+    ///
+    pub fn new(settings: Arc<Mutex<Settings>>) -> Self {
+        Self {
+            settings,
+            interval: DEFAULT_INTERVAL,
+            change_callbacks: vec![],
+            error_callbacks: vec![],
+            failed_file: None,
+        }
+    }
+
+    /// Sets the interval between two polls of the settings file
+    /// Returns the watcher with the new interval
+    ///  * `self` -  The SettingsWatcher struct
+    ///  * `interval` -  The polling interval
+    ///
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Registers a callback called with the changed key/value pairs
+    /// every time the settings file is reloaded and some values changed
+    /// Returns the watcher with the new callback
+    ///  * `self` -  The SettingsWatcher struct
+    ///  * `callback` -  The function receiving the changes
+    ///
+    pub fn on_change<F>(mut self, callback: F) -> Self where F: FnMut(&[SettingChange]) + Send + 'static {
+        self.change_callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Registers a callback called with the error returned by Settings::reload
+    /// when the modified settings file can not be loaded, or with the
+    /// SettingsError::Conflict error when the unsaved changes can not be applied again
+    /// Returns the watcher with the new callback
+    ///  * `self` -  The SettingsWatcher struct
+    ///  * `callback` -  The function receiving the error
+    ///
+    pub fn on_error<F>(mut self, callback: F) -> Self where F: FnMut(&SettingsError) + Send + 'static {
+        self.error_callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Checks once if the settings file has been modified and reloads it,
+    /// callbacks are called from the calling thread
+    /// It can be used in place of SettingsWatcher::start by applications
+    /// having their own main loop
+    /// Returns the changed key/value pairs, empty if the file has not been
+    /// modified or could not be loaded
+    ///  * `self` -  A mutable reference to SettingsWatcher struct
+    ///
+    pub fn check(&mut self) -> Vec<SettingChange> {
+        let result = {
+            let mut settings = match self.settings.lock() {
+                Ok(settings) => settings,
+                Err(poisoned) => poisoned.into_inner()
+            };
+            if !settings.is_modified_on_disk() {
+                return vec![];
+            }
+            let file_metadata = settings.file().and_then(|file| fs::metadata(file).ok())
+                .map(|metadata| (metadata.modified().ok(), metadata.len()));
+            if file_metadata.is_some() && file_metadata == self.failed_file {
+                return vec![];
+            }
+            let old_values = settings.key_values();
+            // unsaved changes are replayed on the reloaded file instead of being lost
+            let reloaded = match settings.file.clone() {
+                Some(file) if settings.is_dirty() => {
+                    let path = settings.path.clone();
+                    settings.merge(&file, path)
+                },
+                _ => settings.reload()
+            };
+            match reloaded {
+                Ok(()) => {
+                    self.failed_file = None;
+                    Ok(Self::changes(old_values, settings.key_values()))
+                },
+                Err(error) => {
+                    self.failed_file = file_metadata;
+                    Err(error)
+                }
+            }
+        };
+        // callbacks are called with Settings unlocked so that they can use it
+        match result {
+            Ok(changes) => {
+                if !changes.is_empty() {
+                    for callback in &mut self.change_callbacks {
+                        callback(&changes);
+                    }
+                }
+                changes
+            },
+            Err(error) => {
+                for callback in &mut self.error_callbacks {
+                    callback(&error);
+                }
+                vec![]
+            }
+        }
+    }
+
+    /// Starts a thread polling the settings file
+    /// Returns the handle used to stop the thread
+    ///  * `self` -  The SettingsWatcher struct
+    ///
+    pub fn start(mut self) -> WatcherHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            while !thread_stop.load(Ordering::SeqCst) {
+                thread::park_timeout(self.interval);
+                if !thread_stop.load(Ordering::SeqCst) {
+                    self.check();
+                }
+            }
+        });
+        WatcherHandle { stop, thread: Some(thread) }
+    }
+
+    // Returns the key/value pairs changed between two lists of
+    // section name, key and value, see Settings::key_values
+    // Values of a key repeated as array elements are compared by position:
+    // the n-th new element is compared with the n-th old one
+    //  * `old_values` -  The key/value pairs before the reload
+    //  * `new_values` -  The key/value pairs after the reload
+    fn changes(old_values: Vec<(String, String, String)>, new_values: Vec<(String, String, String)>) -> Vec<SettingChange> {
+        let mut old_map: HashMap<(String, String), VecDeque<String>> = HashMap::new();
+        let mut old_order: Vec<(String, String)> = vec![];
+        for (section, key, value) in old_values {
+            old_order.push((section.clone(), key.clone()));
+            old_map.entry((section, key)).or_default().push_back(value);
+        }
+        let mut changes = vec![];
+        for (section, key, value) in new_values {
+            let old_value = old_map.get_mut(&(section.clone(), key.clone())).and_then(VecDeque::pop_front);
+            if old_value.as_ref() != Some(&value) {
+                changes.push(SettingChange { section, key, old_value, new_value: Some(value) });
+            }
+        }
+        // old elements left are the removed ones
+        for (section, key) in old_order {
+            if let Some(old_value) = old_map.get_mut(&(section.clone(), key.clone())).and_then(VecDeque::pop_front) {
+                changes.push(SettingChange { section, key, old_value: Some(old_value), new_value: None });
+            }
+        }
+        changes
+    }
+}
+
+/// Handle of the thread started by SettingsWatcher::start,
+/// the thread is stopped when the handle is dropped
+pub struct WatcherHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatcherHandle {
+    /// Stops the thread polling the settings file and waits for its end
+    ///  * `self` -  The WatcherHandle struct
+    ///
+    pub fn stop(mut self) {
+        self.stop_thread();
+    }
+
+    // Signals the polling thread to stop and joins it
+    fn stop_thread(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+// implementation of Drop trait for the WatcherHandle structure
+impl Drop for WatcherHandle {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}